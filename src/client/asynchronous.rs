@@ -0,0 +1,541 @@
+
+use std::time::{ Duration, Instant };
+
+use tokio::{
+    net::{ TcpStream, ToSocketAddrs },
+    time::timeout as to,
+    io::{ AsyncRead, AsyncWrite, AsyncReadExt },
+};
+
+use crate::{
+    msg::{ OuchResponse, OuchRequest },
+    soupbintcp::{
+        ClientPacket,
+        ServerPacket,
+        Username,
+        Password,
+        SessionId,
+    },
+    error::OuchError,
+    types::UserRefNum,
+    risk::RiskGate,
+    throttle::Throttle,
+    kill::{ KillSwitch, KillReport },
+};
+
+use super::{
+    ClientState,
+    outbox::Outbox,
+    OuchReader,
+    OuchWriter,
+    READ_SIZE,
+    check_login,
+    sync_response,
+    unwrap_response,
+};
+
+
+/// Convenience struct for coordinating order entry to an OUCH server
+/// from within a `tokio` runtime.
+/// See `OuchClient` for a blocking client.
+///
+/// The client may use any transport that implements
+/// `AsyncRead + AsyncWrite` (e.g., a TLS stream, a Unix socket,
+/// or `tokio::io::duplex` for testing), but defaults to `TcpStream`.
+pub struct AsyncOuchClient<S = TcpStream> {
+    stream: S,
+    timeout: Duration,
+    buffer: Vec<u8>,
+    outbox: Outbox,
+    state: ClientState,
+}
+
+impl AsyncOuchClient<TcpStream> {
+
+    /// Create an async order entry Client by connecting to the OUCH server
+    /// and logging in to a SoupBinTCP session.
+    /// Use `SessionId::current()` to log in to the currently active session.
+    /// `sequence_number` is the next sequenced message you wish to receive;
+    /// use `0` to start from the most recently generated message.
+    pub async fn login(
+        addr: impl ToSocketAddrs,
+        username: Username,
+        password: Password,
+        session: SessionId,
+        sequence_number: u64,
+        timeout: Duration,
+    ) -> Result<Self, OuchError> {
+
+        let stream = to(timeout, TcpStream::connect(addr)).await??;
+
+        AsyncOuchClient::login_stream(
+            stream,
+            username,
+            password,
+            session,
+            sequence_number,
+            timeout
+        ).await
+    }
+
+    /// Create an async order entry Client by wrapping `tokio::net::TcpStream`.
+    /// Assumes that the login request was accepted and the server is ready
+    /// to receive orders.
+    pub async fn wrap(
+        stream: TcpStream,
+        timeout: Duration,
+    ) -> Result<Self, OuchError> {
+        AsyncOuchClient::wrap_stream(stream, timeout).await
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncOuchClient<S> {
+
+    /// Create an async order entry Client by logging in to a SoupBinTCP
+    /// session over a stream that is already connected to the OUCH server.
+    /// See `login`.
+    pub async fn login_stream(
+        stream: S,
+        username: Username,
+        password: Password,
+        session: SessionId,
+        sequence_number: u64,
+        timeout: Duration,
+    ) -> Result<Self, OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::info!("Creating new AsyncOuchClient instance...");
+        }
+
+        let mut client = AsyncOuchClient::new(stream, timeout);
+
+        #[cfg(feature = "logs")] {
+            log::info!("Logging in to OUCH server...");
+        }
+
+        client.send_packet(ClientPacket::LoginRequest {
+            username,
+            password,
+            session,
+            sequence_number,
+        }).await?;
+
+        loop {
+            let packet = client.recv_packet().await?;
+            if let Some((session, sequence_number)) = check_login(packet)? {
+                client.state.logged_in(session, sequence_number);
+                break
+            }
+        }
+
+        client.sync().await?;
+        Ok(client)
+    }
+
+    /// Create an async order entry Client by wrapping a stream.
+    /// Assumes that the login request was accepted and the server is ready
+    /// to receive orders.
+    pub async fn wrap_stream(
+        stream: S,
+        timeout: Duration,
+    ) -> Result<Self, OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::info!("Creating new AsyncOuchClient instance...");
+        }
+
+        let mut client = AsyncOuchClient::new(stream, timeout);
+        client.sync().await?;
+        Ok(client)
+    }
+
+    fn new(stream: S, timeout: Duration) -> Self {
+        AsyncOuchClient {
+            stream,
+            timeout,
+            buffer: vec![0u8; READ_SIZE],
+            outbox: Outbox::default(),
+            state: ClientState::new(),
+        }
+    }
+
+    /// Split the client into halves that can be moved into separate tasks,
+    /// so that requests can be sent while waiting on `recv`.
+    /// The writer and any number of its clones share the UserRefNum
+    /// sequence, which the reader keeps in sync with the server.
+    pub fn into_split(self) -> (OuchReader<S>, OuchWriter<S>) {
+        super::split::split(
+            self.stream,
+            self.timeout,
+            self.buffer,
+            self.outbox,
+            self.state
+        )
+    }
+
+    /// Use this method to GET the next valid UserRefNum.
+    /// See `OuchClient::new_user_ref_num`.
+    pub fn new_user_ref_num(&mut self) -> UserRefNum {
+        self.state.new_user_ref_num()
+    }
+
+    /// Use this method to PEEK at the next UserRefNum.
+    /// See `OuchClient::next_user_ref_num`.
+    pub fn next_user_ref_num(&self) -> &UserRefNum {
+        &self.state.next_user_ref_num
+    }
+
+    /// Ensure the next UserRefNum is at least `num`.
+    /// See `OuchClient::sync_user_ref_num`.
+    pub fn sync_user_ref_num(&mut self, num: UserRefNum) {
+        self.state.sync_user_ref_num(num)
+    }
+
+    /// The SoupBinTCP session the client is logged in to.
+    /// This is blank if the client was created using `wrap`.
+    pub fn session(&self) -> &SessionId {
+        &self.state.session
+    }
+
+    /// The sequence number of the next sequenced message from the server
+    /// that has not yet been received.
+    /// This is only accurate if the client was created using `login`.
+    pub fn sequence_number(&self) -> u64 {
+        self.state.sequence_number()
+    }
+
+    /// Get mutable access to the stream
+    /// (e.g., to configure it or to send a session management message).
+    /// Treat this carefully so as to not disrupt communication.
+    pub fn stream(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Configure the client to send a Client Heartbeat when nothing has been
+    /// sent to the server for this duration.
+    pub fn set_heartbeat_interval(&mut self, duration: Duration) {
+        self.state.heartbeat_interval = duration
+    }
+
+    /// A Client Heartbeat is sent when nothing has been sent to the server
+    /// for this duration. Defaults to one second.
+    pub fn heartbeat_interval(&self) -> &Duration {
+        &self.state.heartbeat_interval
+    }
+
+    /// Configure how long the server may be silent before the connection
+    /// is presumed lost. Defaults to fifteen seconds.
+    pub fn set_heartbeat_timeout(&mut self, duration: Duration) {
+        self.state.heartbeat_timeout = duration
+    }
+
+    /// The server may be silent for this duration before the connection
+    /// is presumed lost.
+    pub fn heartbeat_timeout(&self) -> &Duration {
+        &self.state.heartbeat_timeout
+    }
+
+    /// Check every request sent through `send` with a `RiskGate`.
+    /// Refused requests are returned as `OuchError::RiskRejected`.
+    pub fn set_risk_gate(&mut self, gate: RiskGate) {
+        self.state.risk = Some(gate)
+    }
+
+    /// Get mutable access to the risk gate
+    /// (e.g., to update its reference prices).
+    pub fn risk_gate(&mut self) -> Option<&mut RiskGate> {
+        self.state.risk.as_mut()
+    }
+
+    // Used by the supervisor to carry the risk gate across reconnects.
+    pub(crate) fn take_risk_gate(&mut self) -> Option<RiskGate> {
+        self.state.risk.take()
+    }
+
+    /// Limit the rate of requests sent through `send` with a `Throttle`.
+    /// Requests over the limit wait in `send` or are returned as
+    /// `OuchError::Throttled`, depending on its `ThrottleMode`.
+    pub fn set_throttle(&mut self, throttle: Throttle) {
+        self.state.throttle = Some(throttle)
+    }
+
+    /// Get mutable access to the throttle (e.g., to change its limits).
+    pub fn throttle(&mut self) -> Option<&mut Throttle> {
+        self.state.throttle.as_mut()
+    }
+
+    // Used by the supervisor to carry the throttle across reconnects.
+    pub(crate) fn take_throttle(&mut self) -> Option<Throttle> {
+        self.state.throttle.take()
+    }
+
+    /// Configure the stream to time out after this duration.
+    pub fn set_timeout(&mut self, duration: Duration) {
+        self.timeout = duration
+    }
+
+    /// The stream is configured to time out after this duration.
+    pub fn timeout(&self) -> &Duration { &self.timeout }
+
+    /// Send a heartbeat if nothing has been sent recently,
+    /// and check that the server has not gone silent.
+    /// This is done automatically while waiting in `recv`,
+    /// so only call this if the client may go long periods without `recv`.
+    pub async fn keep_alive(&mut self) -> Result<(), OuchError> {
+
+        // Finish writing any packet that was interrupted,
+        // unless nothing of it was written.
+        self.discard_unsent();
+        if !self.outbox.is_empty() {
+            self.flush().await?;
+        }
+
+        if self.state.heartbeat_due() {
+            self.send_packet(ClientPacket::ClientHeartbeat).await?;
+        }
+
+        self.state.check_heartbeat()
+    }
+
+    // Send AccountQuery to verify connection & get next UserRefNum.
+    async fn sync(&mut self) -> Result<(), OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::info!("Syncing account with server...");
+        }
+
+        self.send(crate::account_query!()).await?;
+
+        // Messages replayed after login will arrive ahead of the response.
+        loop {
+            let response = self.recv_response().await?;
+            if sync_response(&response) {
+                self.state.observe(&response);
+                return Ok(())
+            }
+            self.state.pending.push_back(response);
+        }
+    }
+
+    /// Log out of the SoupBinTCP session.
+    /// Any OUCH responses received before the session ends are returned.
+    pub async fn logout(mut self) -> Result<Vec<OuchResponse>, OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::info!("Logging out of OUCH server...");
+        }
+
+        self.send_packet(ClientPacket::LogoutRequest).await?;
+
+        let mut responses = Vec::new();
+        loop {
+            match self.recv().await {
+                Ok(response) => responses.push(response),
+                Err(OuchError::EndOfSession) => break,
+                Err(error) => return Err(error),
+            }
+        }
+
+        #[cfg(feature = "logs")] {
+            log::info!("Logout successful.");
+        }
+
+        Ok(responses)
+    }
+
+    /// Send OUCH message to the server.
+    /// See `send_packet` regarding cancel safety.
+    pub async fn send(&mut self, msg: OuchRequest) -> Result<(), OuchError> {
+
+        // Requests refused by the risk gate do not use up the throttle.
+        self.state.check_request(&msg)?;
+        while let Some(wait) = self.state.throttle_request(&msg)? {
+            tokio::time::sleep(wait).await;
+        }
+
+        #[cfg(feature = "logs")] {
+            log::debug!("Sending {} request to server...", &msg);
+        }
+
+        self.send_packet(ClientPacket::UnsequencedData(msg)).await?;
+
+        #[cfg(feature = "logs")] {
+            log::debug!("Request sent.");
+        }
+
+        Ok(())
+    }
+
+    /// Send a SoupBinTCP packet to the server.
+    /// OUCH requests should be sent using `send` instead.
+    ///
+    /// # Cancel safety
+    /// If cancelled (or timed out) before any of the packet is written,
+    /// the packet is dropped and never sent.
+    /// If it has been partly written, the rest is written before anything
+    /// else is sent, so the stream is never left with a partial packet.
+    /// A request is tracked by the risk gate from when it is queued,
+    /// until it is dropped.
+    pub async fn send_packet(
+        &mut self,
+        packet: ClientPacket
+    ) -> Result<(), OuchError> {
+
+        let bytes = packet.encode()?;
+        self.discard_unsent();
+        self.state.track_packet(&packet);
+        self.outbox.push(bytes, packet);
+
+        self.flush().await.inspect_err(|_| self.discard_unsent())
+    }
+
+    // Drop a packet whose send was interrupted before any of it was written.
+    fn discard_unsent(&mut self) {
+        let dropped = self.outbox.discard();
+        self.state.untrack_packet(dropped);
+    }
+
+    // Write out the packets that have been queued for sending.
+    async fn flush(&mut self) -> Result<(), OuchError> {
+
+        to(self.timeout, self.outbox.flush(&mut self.stream)).await??;
+        self.state.sent();
+
+        Ok(())
+    }
+
+    /// Receive OUCH message from the server.
+    /// Heartbeats and debug packets from the server are skipped.
+    ///
+    /// # Cancel safety
+    /// This method is cancellation safe, so it may be used in `select!`
+    /// or with a timeout. Bytes already read are kept in the client,
+    /// and the next call resumes where the cancelled call left off.
+    pub async fn recv(&mut self) -> Result<OuchResponse, OuchError> {
+
+        let response = match self.state.take_pending() {
+            Some(response) => response,
+            None => self.recv_response().await?,
+        };
+        self.state.observe(&response);
+
+        Ok(response)
+    }
+
+    async fn recv_response(&mut self) -> Result<OuchResponse, OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::debug!("Waiting for response from server...");
+        }
+
+        loop {
+            let packet = self.recv_packet().await?;
+            if let Some(response) = unwrap_response(packet)? {
+
+                #[cfg(feature = "logs")] {
+                    log::debug!("Response recieved.");
+                }
+
+                return Ok(response)
+            }
+        }
+    }
+
+    /// Receive the next SoupBinTCP packet from the server.
+    /// Heartbeats are sent to the server while waiting.
+    ///
+    /// # Cancel safety
+    /// This method is cancellation safe. See `recv`.
+    pub async fn recv_packet(&mut self) -> Result<ServerPacket, OuchError> {
+
+        let deadline = Instant::now() + self.timeout;
+
+        loop {
+            // Bytes left over from a previous read may hold a whole packet.
+            if let Some(packet) = self.state.next_packet()? {
+                return Ok(packet)
+            }
+
+            self.keep_alive().await?;
+
+            // Wake up at least once per heartbeat interval to keep alive.
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let wait = remaining.min(self.state.heartbeat_interval);
+
+            let n = match to(wait, self.stream.read(&mut self.buffer)).await {
+                Ok(n) => n?,
+                Err(elapsed) if Instant::now() >= deadline => {
+                    return Err(elapsed.into())
+                },
+                Err(_) => continue,
+            };
+
+            if n == 0 {
+                return Err(std::io::Error::from(
+                    std::io::ErrorKind::UnexpectedEof
+                ).into())
+            }
+
+            self.state.extend(&self.buffer[..n]);
+        }
+    }
+
+    /// Cancel every order in the symbols of a `KillSwitch`
+    /// (including any open orders of the risk gate),
+    /// optionally disable order entry, and wait for the server to
+    /// acknowledge each request until the kill switch times out.
+    /// A failure to send a request or to receive a packet is recorded in
+    /// the report, rather than stopping the remaining requests.
+    /// Every response received while waiting is still returned by `recv`.
+    pub async fn kill_switch(
+        &mut self,
+        kill: &KillSwitch
+    ) -> Result<KillReport, OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::warn!("Kill switch activated, canceling all orders...");
+        }
+
+        let deadline = Instant::now() + *kill.timeout();
+        let (requests, mut report) = kill.requests(
+            self.state.risk.as_ref().map(|gate| gate.orders()),
+            &mut self.state.next_user_ref_num,
+        );
+
+        for request in requests {
+            if let Err(error) = self.send(request.clone()).await {
+                report.send_failed(&request, &error);
+            }
+        }
+
+        while !report.is_settled() {
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let response = match to(remaining, self.recv_response()).await {
+                Ok(Ok(response)) => response,
+                Ok(Err(OuchError::AsyncTimeout(..))) => continue,
+                Ok(Err(error)) => match report.recv_failed(&error) {
+                    true => continue,
+                    false => break,
+                },
+                Err(_) => break,
+            };
+
+            report.acknowledge(&response);
+            self.state.pending.push_back(response);
+        }
+
+        #[cfg(feature = "logs")] {
+            match report.is_complete() {
+                true => log::warn!("Kill switch acknowledged by server."),
+                false => log::error!(
+                    "Kill switch not acknowledged for {:?}",
+                    report.unacknowledged()
+                ),
+            }
+        }
+
+        Ok(report)
+    }
+}
+
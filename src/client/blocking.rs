@@ -0,0 +1,490 @@
+
+use std::{
+    net::{ TcpStream, ToSocketAddrs },
+    io::{ Read, Write },
+    time::{ Duration, Instant },
+};
+
+use crate::{
+    msg::{ OuchResponse, OuchRequest },
+    soupbintcp::{
+        ClientPacket,
+        ServerPacket,
+        Username,
+        Password,
+        SessionId,
+    },
+    error::OuchError,
+    types::UserRefNum,
+    risk::RiskGate,
+    throttle::Throttle,
+    kill::{ KillSwitch, KillReport },
+};
+
+use super::{
+    ClientState,
+    HEARTBEAT_INTERVAL,
+    READ_SIZE,
+    check_login,
+    sync_response,
+    unwrap_response,
+};
+
+
+/// Convenience struct for coordinating order entry to an OUCH server.
+/// This client blocks while waiting on the stream;
+/// see `AsyncOuchClient` (with the `async` feature) for use with `tokio`.
+///
+/// The client may use any transport that implements `Read + Write`
+/// (e.g., a TLS stream or a Unix socket), but defaults to `TcpStream`.
+/// For heartbeats to be sent while waiting in `recv`, reads from the stream
+/// should time out (with `WouldBlock` or `TimedOut`) at least as often
+/// as the heartbeat interval.
+pub struct OuchClient<S = TcpStream> {
+    stream: S,
+    buffer: Vec<u8>,
+    state: ClientState,
+}
+
+impl OuchClient<TcpStream> {
+
+    /// Create an order entry Client by connecting to the OUCH server
+    /// and logging in to a SoupBinTCP session.
+    /// Use `SessionId::current()` to log in to the currently active session.
+    /// `sequence_number` is the next sequenced message you wish to receive;
+    /// use `0` to start from the most recently generated message.
+    pub fn login(
+        addr: impl ToSocketAddrs,
+        username: Username,
+        password: Password,
+        session: SessionId,
+        sequence_number: u64,
+    ) -> Result<Self, OuchError> {
+
+        // Reads time out after the heartbeat interval,
+        // so that `recv` can keep the connection alive while it waits.
+        let stream = TcpStream::connect(addr)?;
+        stream.set_read_timeout(Some(HEARTBEAT_INTERVAL))?;
+
+        OuchClient::login_stream(
+            stream,
+            username,
+            password,
+            session,
+            sequence_number
+        )
+    }
+
+    /// Create an order entry Client by wrapping `std::net::TcpStream`.
+    /// Assumes that the login request was accepted and the server is ready
+    /// to receive orders.
+    pub fn wrap(stream: TcpStream) -> Result<Self, OuchError> {
+
+        stream.set_read_timeout(Some(HEARTBEAT_INTERVAL))?;
+        OuchClient::wrap_stream(stream)
+    }
+}
+
+impl<S: Read + Write> OuchClient<S> {
+
+    /// Create an order entry Client by logging in to a SoupBinTCP session
+    /// over a stream that is already connected to the OUCH server.
+    /// See `login`.
+    pub fn login_stream(
+        stream: S,
+        username: Username,
+        password: Password,
+        session: SessionId,
+        sequence_number: u64,
+    ) -> Result<Self, OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::info!("Creating new OuchClient instance...");
+        }
+
+        let mut client = OuchClient::new(stream);
+
+        #[cfg(feature = "logs")] {
+            log::info!("Logging in to OUCH server...");
+        }
+
+        client.send_packet(ClientPacket::LoginRequest {
+            username,
+            password,
+            session,
+            sequence_number,
+        })?;
+
+        loop {
+            let packet = client.recv_packet()?;
+            if let Some((session, sequence_number)) = check_login(packet)? {
+                client.state.logged_in(session, sequence_number);
+                break
+            }
+        }
+
+        client.sync()?;
+        Ok(client)
+    }
+
+    /// Create an order entry Client by wrapping a stream.
+    /// Assumes that the login request was accepted and the server is ready
+    /// to receive orders.
+    pub fn wrap_stream(stream: S) -> Result<Self, OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::info!("Creating new OuchClient instance...");
+        }
+
+        let mut client = OuchClient::new(stream);
+        client.sync()?;
+        Ok(client)
+    }
+
+    fn new(stream: S) -> Self {
+        OuchClient {
+            stream,
+            buffer: vec![0u8; READ_SIZE],
+            state: ClientState::new(),
+        }
+    }
+
+    /// Use this method to GET the next valid UserRefNum.
+    /// This method automatically increments the number to ensure uniqueness,
+    /// so only call this when you intend to use the value in a message.
+    /// To PEEK at the value of the next UserRefNum without incrementing,
+    /// use the similarly-named method `next_user_ref_num`.
+    pub fn new_user_ref_num(&mut self) -> UserRefNum {
+        self.state.new_user_ref_num()
+    }
+
+    /// Use this method to PEEK at the next UserRefNum.
+    /// This method does NOT automatically increment to ensure uniqueness,
+    /// so do not use it to get a new UserRefNum to use in a message.
+    /// To get the value of the next UserRefNum and increment it,
+    /// use the similarly-named method `new_user_ref_num`.
+    pub fn next_user_ref_num(&self) -> &UserRefNum {
+        &self.state.next_user_ref_num
+    }
+
+    /// Ensure the next UserRefNum is at least `num`.
+    /// The next UserRefNum is never decreased, so that an older value
+    /// (e.g., from a replayed AccountQueryResponse) cannot cause reuse.
+    pub fn sync_user_ref_num(&mut self, num: UserRefNum) {
+        self.state.sync_user_ref_num(num)
+    }
+
+    /// The SoupBinTCP session the client is logged in to.
+    /// This is blank if the client was created using `wrap`.
+    pub fn session(&self) -> &SessionId {
+        &self.state.session
+    }
+
+    /// The sequence number of the next sequenced message from the server
+    /// that has not yet been received (i.e., where to resume after logging
+    /// back in to the session).
+    /// This is only accurate if the client was created using `login`.
+    pub fn sequence_number(&self) -> u64 {
+        self.state.sequence_number()
+    }
+
+    /// Get mutable access to the stream
+    /// (e.g., to configure it or to send a session management message).
+    /// Treat this carefully so as to not disrupt communication.
+    pub fn stream(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Configure the client to send a Client Heartbeat when nothing has been
+    /// sent to the server for this duration.
+    /// Reads from the stream should time out at least this often,
+    /// so that `recv` can send heartbeats while it waits
+    /// (e.g., using `TcpStream::set_read_timeout` through `stream`).
+    pub fn set_heartbeat_interval(&mut self, duration: Duration) {
+        self.state.heartbeat_interval = duration
+    }
+
+    /// A Client Heartbeat is sent when nothing has been sent to the server
+    /// for this duration. Defaults to one second.
+    pub fn heartbeat_interval(&self) -> &Duration {
+        &self.state.heartbeat_interval
+    }
+
+    /// Configure how long the server may be silent before the connection
+    /// is presumed lost. Defaults to fifteen seconds.
+    pub fn set_heartbeat_timeout(&mut self, duration: Duration) {
+        self.state.heartbeat_timeout = duration
+    }
+
+    /// The server may be silent for this duration before the connection
+    /// is presumed lost.
+    pub fn heartbeat_timeout(&self) -> &Duration {
+        &self.state.heartbeat_timeout
+    }
+
+    /// Check every request sent through `send` with a `RiskGate`.
+    /// Refused requests are returned as `OuchError::RiskRejected`.
+    pub fn set_risk_gate(&mut self, gate: RiskGate) {
+        self.state.risk = Some(gate)
+    }
+
+    /// Get mutable access to the risk gate
+    /// (e.g., to update its reference prices).
+    pub fn risk_gate(&mut self) -> Option<&mut RiskGate> {
+        self.state.risk.as_mut()
+    }
+
+    // Used by the supervisor to carry the risk gate across reconnects.
+    pub(crate) fn take_risk_gate(&mut self) -> Option<RiskGate> {
+        self.state.risk.take()
+    }
+
+    /// Limit the rate of requests sent through `send` with a `Throttle`.
+    /// Requests over the limit wait in `send` or are returned as
+    /// `OuchError::Throttled`, depending on its `ThrottleMode`.
+    pub fn set_throttle(&mut self, throttle: Throttle) {
+        self.state.throttle = Some(throttle)
+    }
+
+    /// Get mutable access to the throttle (e.g., to change its limits).
+    pub fn throttle(&mut self) -> Option<&mut Throttle> {
+        self.state.throttle.as_mut()
+    }
+
+    // Used by the supervisor to carry the throttle across reconnects.
+    pub(crate) fn take_throttle(&mut self) -> Option<Throttle> {
+        self.state.throttle.take()
+    }
+
+    /// Send a heartbeat if nothing has been sent recently,
+    /// and check that the server has not gone silent.
+    /// This is done automatically while waiting in `recv`,
+    /// so only call this if the client may go long periods without `recv`.
+    pub fn keep_alive(&mut self) -> Result<(), OuchError> {
+
+        if self.state.heartbeat_due() {
+            self.send_packet(ClientPacket::ClientHeartbeat)?;
+        }
+
+        self.state.check_heartbeat()
+    }
+
+    // Send AccountQuery to verify connection & get next UserRefNum.
+    fn sync(&mut self) -> Result<(), OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::info!("Syncing account with server...");
+        }
+
+        self.send(crate::account_query!())?;
+
+        // Messages replayed after login will arrive ahead of the response.
+        loop {
+            let response = self.recv_response()?;
+            if sync_response(&response) {
+                self.state.observe(&response);
+                return Ok(())
+            }
+            self.state.pending.push_back(response);
+        }
+    }
+
+    /// Log out of the SoupBinTCP session.
+    /// Any OUCH responses received before the session ends are returned.
+    pub fn logout(mut self) -> Result<Vec<OuchResponse>, OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::info!("Logging out of OUCH server...");
+        }
+
+        self.send_packet(ClientPacket::LogoutRequest)?;
+
+        let mut responses = Vec::new();
+        loop {
+            match self.recv() {
+                Ok(response) => responses.push(response),
+                Err(OuchError::EndOfSession) => break,
+                Err(error) => return Err(error),
+            }
+        }
+
+        #[cfg(feature = "logs")] {
+            log::info!("Logout successful.");
+        }
+
+        Ok(responses)
+    }
+
+    /// Send an OUCH order request to the server.
+    pub fn send(&mut self, msg: OuchRequest) -> Result<(), OuchError> {
+
+        // Requests refused by the risk gate do not use up the throttle.
+        self.state.check_request(&msg)?;
+        while let Some(wait) = self.state.throttle_request(&msg)? {
+            std::thread::sleep(wait);
+        }
+
+        #[cfg(feature = "logs")] {
+            log::debug!("Sending {} request to server...", &msg);
+        }
+
+        self.send_packet(ClientPacket::UnsequencedData(msg))?;
+
+        #[cfg(feature = "logs")] {
+            log::debug!("Request sent.");
+        }
+
+        Ok(())
+    }
+
+    /// Send a SoupBinTCP packet to the server.
+    /// OUCH requests should be sent using `send` instead.
+    pub fn send_packet(
+        &mut self,
+        packet: ClientPacket
+    ) -> Result<(), OuchError> {
+
+        let bytes = packet.encode()?;
+        self.stream.write_all(&bytes)?;
+        self.state.sent();
+        self.state.track_packet(&packet);
+
+        Ok(())
+    }
+
+    /// Receive OUCH message from the server.
+    /// Heartbeats and debug packets from the server are skipped.
+    pub fn recv(&mut self) -> Result<OuchResponse, OuchError> {
+
+        let response = match self.state.take_pending() {
+            Some(response) => response,
+            None => self.recv_response()?,
+        };
+        self.state.observe(&response);
+
+        Ok(response)
+    }
+
+    fn recv_response(&mut self) -> Result<OuchResponse, OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::debug!("Waiting for response from server...");
+        }
+
+        loop {
+            let packet = self.recv_packet()?;
+            if let Some(response) = unwrap_response(packet)? {
+
+                #[cfg(feature = "logs")] {
+                    log::debug!("Response recieved.");
+                }
+
+                return Ok(response)
+            }
+        }
+    }
+
+    /// Receive the next SoupBinTCP packet from the server.
+    /// Heartbeats are sent to the server while waiting.
+    pub fn recv_packet(&mut self) -> Result<ServerPacket, OuchError> {
+
+        loop {
+            if let Some(packet) = self.poll_packet()? {
+                return Ok(packet)
+            }
+        }
+    }
+
+    // Take the next whole packet, reading from the stream at most once
+    // (i.e., waiting no longer than the read timeout).
+    fn poll_packet(&mut self) -> Result<Option<ServerPacket>, OuchError> {
+
+        // Bytes left over from a previous read may hold a whole packet.
+        if let Some(packet) = self.state.next_packet()? {
+            return Ok(Some(packet))
+        }
+
+        self.keep_alive()?;
+
+        let n = match self.stream.read(&mut self.buffer) {
+            Ok(n) => n,
+            Err(e) if is_read_timeout(&e) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        if n == 0 {
+            return Err(std::io::Error::from(
+                std::io::ErrorKind::UnexpectedEof
+            ).into())
+        }
+
+        self.state.extend(&self.buffer[..n]);
+        self.state.next_packet()
+    }
+
+    /// Cancel every order in the symbols of a `KillSwitch`
+    /// (including any open orders of the risk gate),
+    /// optionally disable order entry, and wait for the server to
+    /// acknowledge each request until the kill switch times out.
+    /// A failure to send a request or to receive a packet is recorded in
+    /// the report, rather than stopping the remaining requests.
+    ///
+    /// Reads from the stream should time out (see `set_heartbeat_interval`)
+    /// so that waiting can end on time.
+    /// Every response received while waiting is still returned by `recv`.
+    pub fn kill_switch(
+        &mut self,
+        kill: &KillSwitch
+    ) -> Result<KillReport, OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::warn!("Kill switch activated, canceling all orders...");
+        }
+
+        let deadline = Instant::now() + *kill.timeout();
+        let (requests, mut report) = kill.requests(
+            self.state.risk.as_ref().map(|gate| gate.orders()),
+            &mut self.state.next_user_ref_num,
+        );
+
+        for request in requests {
+            if let Err(error) = self.send(request.clone()) {
+                report.send_failed(&request, &error);
+            }
+        }
+
+        while !report.is_settled() && Instant::now() < deadline {
+            let received = self.poll_packet()
+                .and_then(|packet| packet.map_or(Ok(None), unwrap_response));
+
+            match received {
+                Ok(Some(response)) => {
+                    report.acknowledge(&response);
+                    self.state.pending.push_back(response);
+                },
+                Ok(None) => {},
+                Err(error) => if !report.recv_failed(&error) { break },
+            }
+        }
+
+        #[cfg(feature = "logs")] {
+            match report.is_complete() {
+                true => log::warn!("Kill switch acknowledged by server."),
+                false => log::error!(
+                    "Kill switch not acknowledged for {:?}",
+                    report.unacknowledged()
+                ),
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+// The blocking read timed out, which is how `recv` waits for heartbeats.
+fn is_read_timeout(error: &std::io::Error) -> bool {
+
+    use std::io::ErrorKind::*;
+    matches!(error.kind(), WouldBlock | TimedOut)
+}
+
@@ -0,0 +1,300 @@
+
+/// Contains the blocking client, which uses `std::net`.
+mod blocking;
+
+/// Contains the asynchronous client, which uses `tokio`.
+#[cfg(feature = "async")]
+mod asynchronous;
+
+/// Contains the reader and writer halves of the asynchronous client.
+#[cfg(feature = "async")]
+mod split;
+
+/// Contains a write buffer that keeps async sends cancellation safe.
+#[cfg(feature = "async")]
+mod outbox;
+
+pub use self::blocking::OuchClient;
+
+#[cfg(feature = "async")]
+pub use self::{
+    asynchronous::AsyncOuchClient,
+    split::{ OuchReader, OuchWriter },
+};
+
+use std::time::{ Duration, Instant };
+use std::collections::VecDeque;
+
+use crate::{
+    msg::{ OuchRequest, OuchResponse },
+    soupbintcp::{
+        ClientPacket,
+        ServerPacket,
+        PacketBuffer,
+        SessionId,
+    },
+    error::OuchError,
+    types::UserRefNum,
+    risk::RiskGate,
+    throttle::Throttle,
+};
+
+
+// SoupBinTCP expects a heartbeat after one second of silence.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+// The link is presumed lost after this long without hearing from the server.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(15);
+
+// Maximum number of bytes taken from the stream in a single read.
+// Packets larger than this are reassembled over several reads.
+const READ_SIZE: usize = 1024;
+
+
+// Session state shared by the blocking and asynchronous clients,
+// which differ only in how they read from and write to the stream.
+struct ClientState {
+    received: PacketBuffer,
+    pending: VecDeque<OuchResponse>,
+    next_user_ref_num: UserRefNum,
+    session: SessionId,
+    sequence_number: u64,
+    heartbeat_interval: Duration,
+    heartbeat_timeout: Duration,
+    last_sent: Instant,
+    last_received: Instant,
+    risk: Option<RiskGate>,
+    throttle: Option<Throttle>,
+}
+
+impl ClientState {
+
+    fn new() -> Self {
+        ClientState {
+            received: PacketBuffer::new(),
+            pending: VecDeque::new(),
+            next_user_ref_num: UserRefNum::new(),
+            session: SessionId::current(),
+            sequence_number: 0u64,
+            heartbeat_interval: HEARTBEAT_INTERVAL,
+            heartbeat_timeout: HEARTBEAT_TIMEOUT,
+            last_sent: Instant::now(),
+            last_received: Instant::now(),
+            risk: None,
+            throttle: None,
+        }
+    }
+
+    fn new_user_ref_num(&mut self) -> UserRefNum {
+        let new_num = self.next_user_ref_num;
+        self.next_user_ref_num.increment();
+        new_num
+    }
+
+    // The next UserRefNum is never decreased, so that an older value
+    // (e.g., from a replayed AccountQueryResponse) cannot cause reuse.
+    fn sync_user_ref_num(&mut self, num: UserRefNum) {
+        if num.val() > self.next_user_ref_num.val() {
+            self.next_user_ref_num = num
+        }
+    }
+
+    // Responses held for `recv` have not been received by the caller yet.
+    fn sequence_number(&self) -> u64 {
+        self.sequence_number - self.pending.len() as u64
+    }
+
+    // Error if the server has been silent for too long.
+    fn check_heartbeat(&self) -> Result<(), OuchError> {
+
+        let silence = self.last_received.elapsed();
+        if silence >= self.heartbeat_timeout {
+
+            let error = OuchError::HeartbeatTimeout(silence);
+
+            #[cfg(feature = "logs")] {
+                log::error!("OuchClient encountered an error: {}", &error);
+            }
+
+            return Err(error)
+        }
+
+        Ok(())
+    }
+
+    // Check if the client should send a heartbeat to the server.
+    fn heartbeat_due(&self) -> bool {
+        self.last_sent.elapsed() >= self.heartbeat_interval
+    }
+
+    // Responses received ahead of the sync are returned first by `recv`,
+    // and every response is observed to keep the next UserRefNum
+    // (and the risk gate) up-to-date with the server.
+    fn take_pending(&mut self) -> Option<OuchResponse> {
+        self.pending.pop_front()
+    }
+
+    fn observe(&mut self, response: &OuchResponse) {
+        if let OuchResponse::AccountQueryResponse(aqr) = response {
+            self.sync_user_ref_num(aqr.next_user_ref_num());
+        }
+        if let Some(risk) = &mut self.risk {
+            risk.track_response(response);
+        }
+    }
+
+    // Requests over the throttle limit wait for the returned duration
+    // before trying again, unless the throttle refuses them.
+    fn throttle_request(
+        &mut self,
+        request: &OuchRequest
+    ) -> Result<Option<Duration>, OuchError> {
+
+        match &mut self.throttle {
+            Some(throttle) => throttle.admit(request),
+            None => Ok(None),
+        }
+    }
+
+    // Requests refused by the risk gate are never sent.
+    fn check_request(
+        &mut self,
+        request: &OuchRequest
+    ) -> Result<(), OuchError> {
+
+        if let Some(risk) = &mut self.risk {
+            risk.check(request)?;
+        }
+        Ok(())
+    }
+
+    fn logged_in(&mut self, session: SessionId, sequence_number: u64) {
+        self.session = session;
+        self.sequence_number = sequence_number;
+    }
+
+    fn sent(&mut self) {
+        self.last_sent = Instant::now();
+    }
+
+    // Requests are tracked by the risk gate once they are written
+    // (or queued to be written, by the async clients),
+    // so that a request that failed to send is not left open.
+    fn track_packet(&mut self, packet: &ClientPacket) {
+        if let (Some(risk), ClientPacket::UnsequencedData(request)) =
+            (&mut self.risk, packet) {
+            risk.track_request(request);
+        }
+    }
+
+    // A queued request was dropped before any of it was written.
+    #[cfg(feature = "async")]
+    fn untrack_packet(&mut self, packet: Option<ClientPacket>) {
+        if let (Some(risk), Some(ClientPacket::UnsequencedData(request))) =
+            (&mut self.risk, packet) {
+            risk.untrack_request(&request);
+        }
+    }
+
+    fn extend(&mut self, bytes: &[u8]) {
+        self.last_received = Instant::now();
+        self.received.extend(bytes);
+    }
+
+    // Take the next whole packet, tracking the sequence number
+    // of the next message from the server.
+    // A sequenced packet that cannot be parsed still has a sequence number,
+    // so it is counted before the error is returned (otherwise the server
+    // would replay messages already received after a reconnect).
+    fn next_packet(&mut self) -> Result<Option<ServerPacket>, OuchError> {
+
+        let sequenced = self.received.next_is_sequenced();
+        let packet = self.received.next_packet();
+        if sequenced && !matches!(packet, Ok(None)) {
+            self.sequence_number += 1;
+        }
+
+        packet
+    }
+}
+
+// Check the server's reply to a login request.
+// Packets that need no action (heartbeats and debug text) yield `None`.
+fn check_login(
+    packet: ServerPacket
+) -> Result<Option<(SessionId, u64)>, OuchError> {
+
+    use ServerPacket::*;
+    match packet {
+
+        LoginAccepted { session, sequence_number } => {
+
+            #[cfg(feature = "logs")] {
+                log::info!(
+                    "Login accepted for session '{}' at sequence number {}.",
+                    &session,
+                    sequence_number
+                );
+            }
+
+            Ok(Some((session, sequence_number)))
+        },
+
+        LoginRejected(reason) => {
+
+            let error = OuchError::LoginRejected(reason);
+
+            #[cfg(feature = "logs")] {
+                log::error!("OuchClient encountered an error: {}", &error);
+            }
+
+            Err(error)
+        },
+
+        ServerHeartbeat | Debug(..) => Ok(None),
+
+        packet => Err(OuchError::UnexpectedPacket(packet.packet_type())),
+    }
+}
+
+// Check for the server's reply to the initial AccountQuery.
+fn sync_response(response: &OuchResponse) -> bool {
+
+    if let OuchResponse::AccountQueryResponse(..) = response {
+
+        #[cfg(feature = "logs")] {
+            log::info!("Sync successful, new OuchClient connected.");
+        }
+
+        return true
+    }
+
+    false
+}
+
+// Extract the OUCH response carried by a SoupBinTCP packet.
+// Packets that need no action (heartbeats and debug text) yield `None`.
+fn unwrap_response(
+    packet: ServerPacket
+) -> Result<Option<OuchResponse>, OuchError> {
+
+    use ServerPacket::*;
+    match packet {
+
+        SequencedData(response) => Ok(Some(response)),
+
+        ServerHeartbeat | Debug(..) => Ok(None),
+
+        EndOfSession => {
+
+            #[cfg(feature = "logs")] {
+                log::warn!("OuchClient session was ended by the server.");
+            }
+
+            Err(OuchError::EndOfSession)
+        },
+
+        packet => Err(OuchError::UnexpectedPacket(packet.packet_type())),
+    }
+}
+
@@ -0,0 +1,76 @@
+use tokio::io::{ AsyncWrite, AsyncWriteExt };
+
+use crate::soupbintcp::ClientPacket;
+
+
+// Bytes waiting to be written to the server.
+// `write_all` is not cancellation safe: if it is interrupted
+// (e.g., by a timeout or a `select!` branch), part of a packet may have
+// been written with no record of the rest, desynchronising the stream.
+// Instead, packets are queued here and written a piece at a time,
+// so that an interrupted packet is finished before anything else is sent.
+// A packet of which nothing was written when its send was interrupted
+// is dropped instead, since the caller was told that it failed;
+// it is handed back, so that a request can be untracked.
+#[derive(Debug, Default)]
+pub(super) struct Outbox {
+    // The rest of a packet that has been partly written.
+    started: Vec<u8>,
+    // The next packet (and its encoding),
+    // of which nothing has been written yet.
+    unsent: Option<(Vec<u8>, ClientPacket)>,
+}
+
+impl Outbox {
+
+    // Queue a packet, once any earlier packet that was never started
+    // has been discarded.
+    pub(super) fn push(&mut self, bytes: Vec<u8>, packet: ClientPacket) {
+        self.unsent = Some((bytes, packet))
+    }
+
+    // Drop the queued packet, unless part of it has been written.
+    pub(super) fn discard(&mut self) -> Option<ClientPacket> {
+        self.unsent.take().map(|(_, packet)| packet)
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.started.is_empty() && self.unsent.is_none()
+    }
+
+    // Write everything in the outbox to the stream.
+    // If cancelled, any bytes not yet written are kept for the next flush.
+    pub(super) async fn flush<W: AsyncWrite + Unpin>(
+        &mut self,
+        stream: &mut W
+    ) -> std::io::Result<()> {
+
+        loop {
+
+            // Unlike `write_all`, `write` writes nothing if cancelled.
+            if !self.started.is_empty() {
+                let n = stream.write(&self.started).await?;
+                if n == 0 {
+                    return Err(std::io::ErrorKind::WriteZero.into())
+                }
+
+                self.started.drain(..n);
+                continue
+            }
+
+            let Some((unsent, _)) = &self.unsent else { break };
+            let n = stream.write(unsent).await?;
+            if n == 0 {
+                return Err(std::io::ErrorKind::WriteZero.into())
+            }
+
+            // Once part of the packet is written, the rest must follow.
+            let (mut started, _) = self.unsent.take()
+                .expect("Packet was just written");
+            started.drain(..n);
+            self.started = started;
+        }
+
+        stream.flush().await
+    }
+}
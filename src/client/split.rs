@@ -0,0 +1,430 @@
+
+use std::sync::{ Arc, Mutex as SyncMutex, MutexGuard, PoisonError };
+use std::time::{ Duration, Instant };
+
+use tokio::{
+    sync::Mutex,
+    time::timeout as to,
+    io::{
+        AsyncRead,
+        AsyncWrite,
+        AsyncReadExt,
+        ReadHalf,
+        WriteHalf,
+    },
+};
+
+use crate::{
+    msg::{ OuchResponse, OuchRequest },
+    soupbintcp::{
+        ClientPacket,
+        ServerPacket,
+        SessionId,
+    },
+    error::OuchError,
+    types::{ UserRefNum, UserRefNumGenerator },
+    risk::RiskGate,
+    throttle::Throttle,
+};
+
+use super::{
+    ClientState,
+    outbox::Outbox,
+    unwrap_response,
+};
+
+
+// The write half is shared, so that the reader can send heartbeats
+// while it waits for the server.
+// Requests are tracked by the risk gate from when they are queued,
+// until they are dropped.
+struct Sender<S> {
+    stream: WriteHalf<S>,
+    timeout: Duration,
+    outbox: Outbox,
+    risk: Option<SharedGate>,
+    last_sent: Instant,
+}
+
+impl<S: AsyncWrite> Sender<S> {
+
+    async fn send_packet(
+        &mut self,
+        packet: ClientPacket
+    ) -> Result<(), OuchError> {
+
+        let bytes = packet.encode()?;
+        self.queue(bytes, packet);
+        self.flush_queued().await
+    }
+
+    fn queue(&mut self, bytes: Vec<u8>, packet: ClientPacket) {
+
+        self.discard_unsent();
+        if let (Some(gate), ClientPacket::UnsequencedData(request)) =
+            (&self.risk, &packet) {
+            lock(gate).track_request(request);
+        }
+        self.outbox.push(bytes, packet);
+    }
+
+    async fn flush_queued(&mut self) -> Result<(), OuchError> {
+        self.flush().await.inspect_err(|_| self.discard_unsent())
+    }
+
+    // Drop a packet whose send was interrupted before any of it was written.
+    fn discard_unsent(&mut self) {
+        if let (Some(gate), Some(ClientPacket::UnsequencedData(request))) =
+            (&self.risk, self.outbox.discard()) {
+            lock(gate).untrack_request(&request);
+        }
+    }
+
+    async fn flush(&mut self) -> Result<(), OuchError> {
+
+        to(self.timeout, self.outbox.flush(&mut self.stream)).await??;
+        self.last_sent = Instant::now();
+
+        Ok(())
+    }
+}
+
+
+// The risk gate is shared, so that the writer can check requests
+// against the responses received by the reader.
+// The throttle is shared by every clone of the writer.
+// Both are only locked briefly (and never across an await).
+type SharedGate = Arc<SyncMutex<RiskGate>>;
+type SharedThrottle = Arc<SyncMutex<Throttle>>;
+
+fn lock<T>(shared: &SyncMutex<T>) -> MutexGuard<'_, T> {
+    shared.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// A request that has been checked and tracked by the risk gate
+// under one lock, so that clones of the writer cannot each pass a limit
+// before the other's request counts toward it.
+// It is untracked again unless it is queued to be sent
+// (e.g., if the throttle refuses it or the send is cancelled).
+struct Reserved<'a> {
+    gate: Option<&'a SharedGate>,
+    request: OuchRequest,
+    queued: bool,
+}
+
+impl<'a> Reserved<'a> {
+
+    fn new(
+        gate: Option<&'a SharedGate>,
+        request: OuchRequest
+    ) -> Result<Self, OuchError> {
+
+        if let Some(gate) = gate {
+            lock(gate).check_and_track(&request)?;
+        }
+
+        Ok(Reserved { gate, request, queued: false })
+    }
+}
+
+impl Drop for Reserved<'_> {
+    fn drop(&mut self) {
+        if let (Some(gate), false) = (self.gate, self.queued) {
+            lock(gate).untrack_request(&self.request);
+        }
+    }
+}
+
+
+// Any responses already received are kept by the reader.
+pub(super) fn split<S: AsyncRead + AsyncWrite>(
+    stream: S,
+    timeout: Duration,
+    buffer: Vec<u8>,
+    outbox: Outbox,
+    mut state: ClientState,
+) -> (OuchReader<S>, OuchWriter<S>) {
+
+    let (read_half, write_half) = tokio::io::split(stream);
+
+    let risk = state.risk.take().map(|gate| Arc::new(SyncMutex::new(gate)));
+    let throttle = state.throttle.take()
+        .map(|throttle| Arc::new(SyncMutex::new(throttle)));
+
+    let user_ref_nums = UserRefNumGenerator::new(state.next_user_ref_num);
+
+    let sender = Arc::new(Mutex::new(Sender {
+        stream: write_half,
+        timeout,
+        outbox,
+        risk: risk.clone(),
+        last_sent: state.last_sent,
+    }));
+
+    let reader = OuchReader {
+        stream: read_half,
+        sender: sender.clone(),
+        user_ref_nums: user_ref_nums.clone(),
+        risk: risk.clone(),
+        timeout,
+        buffer,
+        state,
+    };
+
+    let writer = OuchWriter { sender, user_ref_nums, risk, throttle };
+
+    (reader, writer)
+}
+
+
+/// Sending half of an `AsyncOuchClient`, created by `into_split`.
+/// The writer may be cloned to send from several tasks.
+pub struct OuchWriter<S> {
+    sender: Arc<Mutex<Sender<S>>>,
+    user_ref_nums: UserRefNumGenerator,
+    risk: Option<SharedGate>,
+    throttle: Option<SharedThrottle>,
+}
+
+impl<S> Clone for OuchWriter<S> {
+    fn clone(&self) -> Self {
+        OuchWriter {
+            sender: self.sender.clone(),
+            user_ref_nums: self.user_ref_nums.clone(),
+            risk: self.risk.clone(),
+            throttle: self.throttle.clone(),
+        }
+    }
+}
+
+impl<S: AsyncWrite> OuchWriter<S> {
+
+    /// Use this method to GET the next valid UserRefNum.
+    /// See `OuchClient::new_user_ref_num`.
+    pub fn new_user_ref_num(&self) -> UserRefNum {
+        self.user_ref_nums.new_user_ref_num()
+    }
+
+    /// Get a handle to the shared UserRefNum sequence,
+    /// which can be cloned and given to any task that creates requests.
+    /// The reader keeps it in sync with any AccountQueryResponse.
+    pub fn user_ref_nums(&self) -> UserRefNumGenerator {
+        self.user_ref_nums.clone()
+    }
+
+    /// Get access to the risk gate shared by both halves, if the client
+    /// had one (e.g., to update its reference prices).
+    /// Do not hold on to it across an await.
+    pub fn risk_gate(&self) -> Option<MutexGuard<'_, RiskGate>> {
+        self.risk.as_deref().map(lock)
+    }
+
+    /// Get access to the throttle shared by every clone of the writer,
+    /// if the client had one. Do not hold on to it across an await.
+    pub fn throttle(&self) -> Option<MutexGuard<'_, Throttle>> {
+        self.throttle.as_deref().map(lock)
+    }
+
+    /// Send OUCH message to the server.
+    /// See `send_packet` regarding cancel safety.
+    ///
+    /// Requests held by the throttle do not block the other clones
+    /// of the writer, so cancels sent from another task go ahead of them.
+    /// A request counts toward the limits of the risk gate from when it is
+    /// checked, so that clones cannot each pass a limit at the same time,
+    /// unless it is refused by the throttle or its send is cancelled
+    /// before any of it is written.
+    pub async fn send(&self, msg: OuchRequest) -> Result<(), OuchError> {
+
+        // Requests refused by the risk gate do not use up the throttle.
+        let mut reserved = Reserved::new(self.risk.as_ref(), msg)?;
+
+        if let Some(throttle) = &self.throttle {
+            loop {
+                let admitted = lock(throttle).admit(&reserved.request)?;
+                match admitted {
+                    Some(wait) => tokio::time::sleep(wait).await,
+                    None => break,
+                }
+            }
+        }
+
+        #[cfg(feature = "logs")] {
+            log::debug!("Sending {} request to server...", &reserved.request);
+        }
+
+        let packet = ClientPacket::UnsequencedData(reserved.request.clone());
+        let bytes = packet.encode()?;
+
+        let mut sender = self.sender.lock().await;
+        sender.queue(bytes, packet);
+        reserved.queued = true;
+        sender.flush_queued().await?;
+
+        #[cfg(feature = "logs")] {
+            log::debug!("Request sent.");
+        }
+
+        Ok(())
+    }
+
+    /// Send a SoupBinTCP packet to the server.
+    /// OUCH requests should be sent using `send` instead.
+    ///
+    /// # Cancel safety
+    /// If cancelled (or timed out) before any of the packet is written,
+    /// the packet is dropped and never sent.
+    /// If it has been partly written, the rest is written before anything
+    /// else is sent, so the stream is never left with a partial packet.
+    /// A request is tracked by the risk gate from when it is queued,
+    /// until it is dropped.
+    pub async fn send_packet(
+        &self,
+        packet: ClientPacket
+    ) -> Result<(), OuchError> {
+
+        self.sender.lock().await.send_packet(packet).await
+    }
+
+    /// Request to log out of the SoupBinTCP session.
+    /// The reader returns `OuchError::EndOfSession` when the session ends.
+    pub async fn logout(&self) -> Result<(), OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::info!("Logging out of OUCH server...");
+        }
+
+        self.send_packet(ClientPacket::LogoutRequest).await
+    }
+}
+
+
+/// Receiving half of an `AsyncOuchClient`, created by `into_split`.
+/// Heartbeats are sent to the server (through the writer's stream)
+/// while waiting in `recv`.
+pub struct OuchReader<S> {
+    stream: ReadHalf<S>,
+    sender: Arc<Mutex<Sender<S>>>,
+    user_ref_nums: UserRefNumGenerator,
+    risk: Option<SharedGate>,
+    timeout: Duration,
+    buffer: Vec<u8>,
+    state: ClientState,
+}
+
+impl<S: AsyncRead + AsyncWrite> OuchReader<S> {
+
+    /// The SoupBinTCP session the client is logged in to.
+    pub fn session(&self) -> &SessionId {
+        &self.state.session
+    }
+
+    /// The sequence number of the next sequenced message from the server
+    /// that has not yet been received.
+    pub fn sequence_number(&self) -> u64 {
+        self.state.sequence_number()
+    }
+
+    /// Configure how long `recv` may wait before timing out.
+    pub fn set_timeout(&mut self, duration: Duration) {
+        self.timeout = duration
+    }
+
+    /// `recv` times out after this duration.
+    pub fn timeout(&self) -> &Duration { &self.timeout }
+
+    /// Send a heartbeat if nothing has been sent recently,
+    /// and check that the server has not gone silent.
+    /// This is done automatically while waiting in `recv`.
+    pub async fn keep_alive(&mut self) -> Result<(), OuchError> {
+
+        let mut sender = self.sender.lock().await;
+
+        // Finish writing any packet that was interrupted,
+        // unless nothing of it was written.
+        sender.discard_unsent();
+        if !sender.outbox.is_empty() {
+            sender.flush().await?;
+        }
+
+        if sender.last_sent.elapsed() >= self.state.heartbeat_interval {
+            sender.send_packet(ClientPacket::ClientHeartbeat).await?;
+        }
+
+        self.state.check_heartbeat()
+    }
+
+    /// Receive OUCH message from the server.
+    /// Heartbeats and debug packets from the server are skipped.
+    ///
+    /// # Cancel safety
+    /// This method is cancellation safe, so it may be used in `select!`
+    /// or with a timeout. Bytes already read are kept in the client,
+    /// and the next call resumes where the cancelled call left off.
+    pub async fn recv(&mut self) -> Result<OuchResponse, OuchError> {
+
+        let response = match self.state.take_pending() {
+            Some(response) => response,
+            None => self.recv_response().await?,
+        };
+
+        if let OuchResponse::AccountQueryResponse(aqr) = &response {
+            self.user_ref_nums.sync_user_ref_num(aqr.next_user_ref_num());
+        }
+        if let Some(gate) = &self.risk {
+            lock(gate).track_response(&response);
+        }
+
+        Ok(response)
+    }
+
+    async fn recv_response(&mut self) -> Result<OuchResponse, OuchError> {
+
+        loop {
+            let packet = self.recv_packet().await?;
+            if let Some(response) = unwrap_response(packet)? {
+                return Ok(response)
+            }
+        }
+    }
+
+    /// Receive the next SoupBinTCP packet from the server.
+    /// Heartbeats are sent to the server while waiting.
+    ///
+    /// # Cancel safety
+    /// This method is cancellation safe. See `recv`.
+    pub async fn recv_packet(&mut self) -> Result<ServerPacket, OuchError> {
+
+        let deadline = Instant::now() + self.timeout;
+
+        loop {
+            // Bytes left over from a previous read may hold a whole packet.
+            if let Some(packet) = self.state.next_packet()? {
+                return Ok(packet)
+            }
+
+            self.keep_alive().await?;
+
+            // Wake up at least once per heartbeat interval to keep alive.
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let wait = remaining.min(self.state.heartbeat_interval);
+
+            let n = match to(wait, self.stream.read(&mut self.buffer)).await {
+                Ok(n) => n?,
+                Err(elapsed) if Instant::now() >= deadline => {
+                    return Err(elapsed.into())
+                },
+                Err(_) => continue,
+            };
+
+            if n == 0 {
+                return Err(std::io::Error::from(
+                    std::io::ErrorKind::UnexpectedEof
+                ).into())
+            }
+
+            self.state.extend(&self.buffer[..n]);
+        }
+    }
+}
+
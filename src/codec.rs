@@ -0,0 +1,226 @@
+
+use bytes::{ Buf, BytesMut };
+use tokio_util::codec::{ Decoder, Encoder };
+
+use crate::{
+    msg::{ OuchRequest, OuchResponse },
+    soupbintcp::{
+        ClientPacket,
+        ServerPacket,
+        SessionId,
+        take_packet,
+        is_sequenced,
+    },
+    error::OuchError,
+};
+
+
+/// Frames OUCH messages in SoupBinTCP packets for the client side
+/// of a connection, so that any stream can be wrapped in
+/// `tokio_util::codec::Framed` to get a `Stream` of responses
+/// and a `Sink` of requests.
+///
+/// Heartbeats and debug packets from the server are skipped,
+/// and a Login Accepted packet is recorded rather than returned.
+/// Client heartbeats are not sent automatically;
+/// send `ClientPacket::ClientHeartbeat` when nothing has been sent recently.
+#[derive(Debug, Clone, Default)]
+pub struct OuchCodec {
+    session: SessionId,
+    sequence_number: u64,
+}
+
+impl OuchCodec {
+
+    /// Create a new codec.
+    pub fn new() -> Self {
+        OuchCodec::default()
+    }
+
+    /// The SoupBinTCP session from the last Login Accepted packet.
+    pub fn session(&self) -> &SessionId {
+        &self.session
+    }
+
+    /// The sequence number of the next sequenced message from the server.
+    /// This is only accurate if the login was decoded by this codec.
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+}
+
+impl Decoder for OuchCodec {
+
+    type Item = OuchResponse;
+    type Error = OuchError;
+
+    fn decode(
+        &mut self,
+        src: &mut BytesMut
+    ) -> Result<Option<OuchResponse>, OuchError> {
+
+        loop {
+            let sequenced = is_sequenced(src);
+            let (consumed, result) = take_packet(src, ServerPacket::parse);
+            src.advance(consumed);
+
+            // A sequenced packet that cannot be parsed is still counted,
+            // so that the sequence number stays in step with the server.
+            if sequenced && result.is_err() {
+                self.sequence_number += 1;
+            }
+
+            let Some(packet) = result? else { return Ok(None) };
+
+            use ServerPacket::*;
+            match packet {
+
+                SequencedData(response) => {
+                    self.sequence_number += 1;
+                    return Ok(Some(response))
+                },
+
+                LoginAccepted { session, sequence_number } => {
+                    self.session = session;
+                    self.sequence_number = sequence_number;
+                },
+
+                ServerHeartbeat | Debug(..) => {},
+
+                LoginRejected(reason) => {
+                    return Err(OuchError::LoginRejected(reason))
+                },
+
+                EndOfSession => return Err(OuchError::EndOfSession),
+
+                packet => return Err(
+                    OuchError::UnexpectedPacket(packet.packet_type())
+                ),
+            }
+        }
+    }
+}
+
+impl Encoder<OuchRequest> for OuchCodec {
+
+    type Error = OuchError;
+
+    fn encode(
+        &mut self,
+        item: OuchRequest,
+        dst: &mut BytesMut
+    ) -> Result<(), OuchError> {
+        dst.extend(ClientPacket::UnsequencedData(item).encode()?);
+        Ok(())
+    }
+}
+
+impl Encoder<ClientPacket> for OuchCodec {
+
+    type Error = OuchError;
+
+    fn encode(
+        &mut self,
+        item: ClientPacket,
+        dst: &mut BytesMut
+    ) -> Result<(), OuchError> {
+        dst.extend(item.encode()?);
+        Ok(())
+    }
+}
+
+
+/// Frames OUCH messages in SoupBinTCP packets for the server side
+/// of a connection (e.g., for a test gateway).
+///
+/// Heartbeats and debug packets from the client are skipped.
+/// A Logout Request ends the stream with `OuchError::EndOfSession`.
+/// Login requests are not expected, so the login should be handled
+/// before framing the stream (see `ClientPacket::parse`).
+#[derive(Debug, Clone, Default)]
+pub struct OuchServerCodec {
+    sequence_number: u64,
+}
+
+impl OuchServerCodec {
+
+    /// Create a new codec.
+    /// `sequence_number` is that of the next sequenced message to be sent.
+    pub fn new(sequence_number: u64) -> Self {
+        OuchServerCodec { sequence_number }
+    }
+
+    /// The sequence number of the next sequenced message to be sent.
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+}
+
+impl Decoder for OuchServerCodec {
+
+    type Item = OuchRequest;
+    type Error = OuchError;
+
+    fn decode(
+        &mut self,
+        src: &mut BytesMut
+    ) -> Result<Option<OuchRequest>, OuchError> {
+
+        loop {
+            let (consumed, result) = take_packet(src, ClientPacket::parse);
+            src.advance(consumed);
+
+            let Some(packet) = result? else { return Ok(None) };
+
+            use ClientPacket::*;
+            match packet {
+
+                UnsequencedData(request) => return Ok(Some(request)),
+
+                ClientHeartbeat | Debug(..) => {},
+
+                LogoutRequest => return Err(OuchError::EndOfSession),
+
+                packet => return Err(
+                    OuchError::UnexpectedPacket(packet.packet_type())
+                ),
+            }
+        }
+    }
+}
+
+impl Encoder<OuchResponse> for OuchServerCodec {
+
+    type Error = OuchError;
+
+    fn encode(
+        &mut self,
+        item: OuchResponse,
+        dst: &mut BytesMut
+    ) -> Result<(), OuchError> {
+        dst.extend(ServerPacket::SequencedData(item).encode()?);
+        self.sequence_number += 1;
+        Ok(())
+    }
+}
+
+impl Encoder<ServerPacket> for OuchServerCodec {
+
+    type Error = OuchError;
+
+    fn encode(
+        &mut self,
+        item: ServerPacket,
+        dst: &mut BytesMut
+    ) -> Result<(), OuchError> {
+
+        dst.extend(item.encode()?);
+
+        if let ServerPacket::SequencedData(..) = item {
+            self.sequence_number += 1;
+        }
+
+        Ok(())
+    }
+}
+
@@ -1,5 +1,8 @@
 
 use thiserror::Error;
+use nom::error::ErrorKind;
+
+use crate::types::{ StockSymbol, UserRefNum, HandleInst, CrossType };
 
 /// Errors that occur within this crate.
 #[derive(Error, Debug)]
@@ -15,13 +18,42 @@ pub enum OuchError {
     #[error("OuchClient suffered an IO error: {0}")]
     ClientIo(#[from] std::io::Error),
 
-    #[error("Failed to parse OuchResponse from data")]
-    Parse,
+    #[error("Failed to parse data: {0}")]
+    Parse(#[from] ParseError),
 
     /// This only occurs in the client.
     #[error("OuchClient encountered an unexpected response to initial Query")]
     UnexpectedResponse,
 
+    /// This only occurs in the client.
+    #[error("OuchClient received an unexpected SoupBinTCP packet: {0}")]
+    UnexpectedPacket(char),
+
+    /// The 2-byte length of a SoupBinTCP packet counts at most 65,535
+    /// bytes, including the packet type.
+    #[error("SoupBinTCP packet of {0} bytes is too long to send")]
+    PacketTooLong(usize),
+
+    /// This only occurs in the client.
+    #[error("OuchClient session was ended by the server")]
+    EndOfSession,
+
+    /// This only occurs in the client.
+    #[error("OuchClient login was rejected: {0:?}")]
+    LoginRejected(crate::soupbintcp::LoginRejectReason),
+
+    /// This only occurs in the client.
+    #[error("OuchClient has not heard from the server in {0:?}")]
+    HeartbeatTimeout(std::time::Duration),
+
+    /// This only occurs when a `RiskGate` is in use.
+    #[error("Request was refused by the risk gate: {0}")]
+    RiskRejected(#[from] RiskViolation),
+
+    /// This only occurs when a `Throttle` is in use.
+    #[error("Request was throttled, retry in {0:?}")]
+    Throttled(std::time::Duration),
+
     #[cfg(feature = "async")]
     /// This only occurs in the client.
     #[error("OuchClient timed out: {0}")]
@@ -41,6 +73,201 @@ pub enum BadElementError {
 
     #[error("Invalid value for {0}")]
     InvalidType(#[from] nsdq_util::error::TypeError),
+
+    #[error("GoodTilTime order requires an ExpireTime option")]
+    MissingExpireTime,
+
+    #[error("HandleInst {0:?} cannot be used with CrossType {1:?}")]
+    InvalidHandleInst(HandleInst, CrossType),
+
+    #[error("MaxFloor {max_floor} exceeds order quantity {quantity}")]
+    InvalidMaxFloor { max_floor: u32, quantity: u32 },
+
+    #[error("MinQty {0} is not a round lot")]
+    InvalidMinQty(u32),
+
+    #[error("{0} requires a pegged price type")]
+    UnpeggedOffset(String),
+
+    #[error("SellShort order requires SharesLocated or LocateBroker")]
+    MissingLocate,
+
+    #[error("Missing required field {0}")]
+    MissingField(String),
+
+    #[error("Option with tag {tag} has {length} bytes, more than 254")]
+    OptionTooLong { tag: u8, length: usize },
+
+    #[error("Unknown option has tag {tag}, which is the tag of {name}")]
+    KnownOptionTag { tag: u8, name: String },
+}
+
+/// Describes data that could not be parsed:
+/// which message and field failed, where, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    message_type: Option<char>,
+    field: Option<&'static str>,
+    offset: usize,
+    bytes: Vec<u8>,
 }
 
+/// Why data could not be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+
+    /// The data ended early; more bytes are needed (how many, if known).
+    Incomplete(Option<usize>),
+
+    /// The data is invalid. A message cut short within a complete
+    /// SoupBinTCP packet is invalid, with `ErrorKind::Complete`.
+    Invalid(ErrorKind),
+}
+
+impl ParseError {
+
+    // The element starting at `at` (a suffix of `input`) failed to parse.
+    pub(crate) fn from_nom(
+        err: nom::Err<nom::error::Error<&[u8]>>,
+        input: &[u8],
+        at: &[u8],
+    ) -> Self {
+
+        let kind = match err {
+            nom::Err::Incomplete(nom::Needed::Size(size)) => {
+                ParseErrorKind::Incomplete(Some(size.get()))
+            },
+            nom::Err::Incomplete(nom::Needed::Unknown) => {
+                ParseErrorKind::Incomplete(None)
+            },
+            nom::Err::Error(e) | nom::Err::Failure(e) => {
+                ParseErrorKind::Invalid(e.code)
+            },
+        };
 
+        Self::new(kind, input, input.len() - at.len())
+    }
+
+    pub(crate) fn new(
+        kind: ParseErrorKind,
+        input: &[u8],
+        offset: usize
+    ) -> Self {
+        ParseError {
+            kind,
+            message_type: None,
+            field: None,
+            offset,
+            bytes: input.to_vec(),
+        }
+    }
+
+    // Name the field (or other element) that failed to parse.
+    pub(crate) fn with_field(mut self, field: &'static str) -> Self {
+        self.field = Some(field);
+        self
+    }
+
+    // The error occurred in data starting at `at`, a suffix of `input`.
+    pub(crate) fn within(mut self, input: &[u8], at: &[u8]) -> Self {
+        self.offset += input.len() - at.len();
+        self.bytes = input.to_vec();
+        self
+    }
+
+    // The error occurred in the body of a message, which follows its
+    // type tag at the start of `input`.
+    pub(crate) fn in_message(mut self, tag: u8, input: &[u8]) -> Self {
+        self.message_type = Some(tag as char);
+        self.offset += 1;
+        self.bytes = input.to_vec();
+        self
+    }
+
+    // The data was known to be complete (e.g., a whole packet),
+    // so running out of it is invalid, as with `nom::combinator::complete`.
+    pub(crate) fn complete(mut self) -> Self {
+        if self.is_incomplete() {
+            self.kind = ParseErrorKind::Invalid(ErrorKind::Complete);
+        }
+        self
+    }
+
+    /// Whether the data was incomplete or invalid.
+    pub fn kind(&self) -> ParseErrorKind { self.kind }
+
+    /// More data is needed, so parsing may succeed once it is read.
+    pub fn is_incomplete(&self) -> bool {
+        matches!(self.kind, ParseErrorKind::Incomplete(..))
+    }
+
+    /// Type tag of the OUCH message that failed to parse, if it was known.
+    pub fn message_type(&self) -> Option<char> { self.message_type }
+
+    /// Name of the field that failed to parse, if it was known.
+    pub fn field(&self) -> Option<&'static str> { self.field }
+
+    /// Offset of the failed field from the start of the data
+    /// (i.e., from the type tag of a message, or the start of a packet).
+    pub fn offset(&self) -> usize { self.offset }
+
+    /// Raw data that failed to parse, from the start of the message
+    /// (or packet) through the end of the input.
+    pub fn bytes(&self) -> &[u8] { &self.bytes }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+
+        match self.kind {
+            ParseErrorKind::Incomplete(..) => write!(f, "incomplete")?,
+            ParseErrorKind::Invalid(code) => {
+                write!(f, "invalid ({})", code.description())?
+            },
+        }
+
+        if let Some(field) = self.field {
+            write!(f, " field {}", field)?;
+        }
+        if let Some(message_type) = self.message_type {
+            write!(f, " in '{}' message", message_type)?;
+        }
+        write!(f, " at byte {}", self.offset)?;
+
+        if let ParseErrorKind::Incomplete(Some(size)) = self.kind {
+            write!(f, ", {} more bytes needed", size)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Reasons a `RiskGate` refuses a request before it is sent.
+/// Notional values and exposures are in units of $0.0001.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RiskViolation {
+
+    #[error("{0} is a restricted symbol")]
+    RestrictedSymbol(StockSymbol),
+
+    #[error("Quantity {quantity} exceeds the limit of {limit}")]
+    MaxQuantity { quantity: u32, limit: u32 },
+
+    #[error("Notional {notional} exceeds the limit of {limit}")]
+    MaxNotional { notional: u64, limit: u64 },
+
+    #[error("Price {price} is outside the collar around {reference}")]
+    PriceCollar { price: u64, reference: u64 },
+
+    #[error("Open exposure in {symbol} of {exposure} exceeds {limit}")]
+    SymbolExposure { symbol: StockSymbol, exposure: u64, limit: u64 },
+
+    #[error("Open exposure of {exposure} exceeds the limit of {limit}")]
+    TotalExposure { exposure: u64, limit: u64 },
+
+    #[error("Order {0:?} is not known, so it cannot be checked")]
+    UnknownOrder(UserRefNum),
+}
@@ -0,0 +1,256 @@
+
+use std::time::Duration;
+
+use crate::{
+    msg::{ OuchRequest, OuchResponse, MassCancel, DisableOrderEntry },
+    orders::OrderManager,
+    error::OuchError,
+    types::{ UserRefNum, StockSymbol, Mpid },
+};
+
+
+// How long to wait for acknowledgements unless configured otherwise.
+const KILL_TIMEOUT: Duration = Duration::from_secs(5);
+
+
+/// Stops all trading on a session in one call:
+/// sends a `MassCancel` for every symbol with live orders,
+/// optionally sends a `DisableOrderEntry` for the firm,
+/// and waits for each request to be acknowledged.
+/// A request that fails to send does not stop the others,
+/// and packets that fail to parse are skipped while waiting;
+/// each failure is recorded in the report.
+///
+/// Pass it to `OuchClient::kill_switch` (or the same method on any client
+/// or supervisor that can receive), which returns a `KillReport`.
+/// Symbols are taken from the orders added with `add_orders`,
+/// along with the open orders of the client's `RiskGate`, if it has one.
+#[derive(Debug, Clone)]
+pub struct KillSwitch {
+    firm: Mpid,
+    symbols: Vec<StockSymbol>,
+    disable_entry: bool,
+    timeout: Duration,
+}
+
+impl KillSwitch {
+
+    /// Create a kill switch for the orders of this firm.
+    pub fn new(firm: Mpid) -> Self {
+        KillSwitch {
+            firm,
+            symbols: Vec::new(),
+            disable_entry: false,
+            timeout: KILL_TIMEOUT,
+        }
+    }
+
+    /// Cancel every order in this symbol.
+    pub fn add_symbol(&mut self, symbol: StockSymbol) {
+        if !self.symbols.contains(&symbol) {
+            self.symbols.push(symbol)
+        }
+    }
+
+    /// Cancel every order in each symbol with an open order.
+    pub fn add_orders(&mut self, orders: &OrderManager) {
+        for order in orders.open_orders() {
+            self.add_symbol(order.symbol())
+        }
+    }
+
+    /// Symbols that will be canceled.
+    pub fn symbols(&self) -> &[StockSymbol] { &self.symbols }
+
+    /// Choose whether to also disable order entry for the firm.
+    /// Defaults to `false`.
+    pub fn set_disable_entry(&mut self, disable: bool) {
+        self.disable_entry = disable
+    }
+
+    /// Configure how long to wait for acknowledgements.
+    /// Defaults to five seconds.
+    pub fn set_timeout(&mut self, duration: Duration) {
+        self.timeout = duration
+    }
+
+    /// Acknowledgements are awaited for this duration.
+    pub fn timeout(&self) -> &Duration { &self.timeout }
+
+    // Build each request, recording what to wait for in the report.
+    // Each request takes (and increments) the next UserRefNum.
+    pub(crate) fn requests(
+        &self,
+        orders: Option<&OrderManager>,
+        next_user_ref_num: &mut UserRefNum,
+    ) -> (Vec<OuchRequest>, KillReport) {
+
+        let mut kill = self.clone();
+        if let Some(orders) = orders {
+            kill.add_orders(orders)
+        }
+
+        let mut requests = Vec::new();
+        let mut report = KillReport::default();
+
+        let mut new_user_ref_num = || {
+            let num = *next_user_ref_num;
+            next_user_ref_num.increment();
+            num
+        };
+
+        for symbol in kill.symbols {
+            let num = new_user_ref_num();
+            requests.push(OuchRequest::MassCancel(
+                MassCancel::new(num, self.firm, symbol)
+            ));
+            report.waiting.push((num, symbol));
+        }
+
+        if self.disable_entry {
+            let num = new_user_ref_num();
+            requests.push(OuchRequest::DisableOrderEntry(
+                DisableOrderEntry::new(num, self.firm)
+            ));
+            report.disabling = Some(num);
+            report.disable_requested = true;
+        }
+
+        (requests, report)
+    }
+}
+
+
+/// Outcome of a `KillSwitch`, listing which requests were acknowledged
+/// by the server before the timeout.
+#[derive(Debug, Clone, Default)]
+pub struct KillReport {
+    acknowledged: Vec<StockSymbol>,
+    waiting: Vec<(UserRefNum, StockSymbol)>,
+    rejected: Vec<StockSymbol>,
+    unsent: Vec<StockSymbol>,
+    disabling: Option<UserRefNum>,
+    disable_requested: bool,
+    entry_disabled: bool,
+    errors: Vec<String>,
+}
+
+impl KillReport {
+
+    /// Symbols whose `MassCancel` was acknowledged.
+    pub fn acknowledged(&self) -> &[StockSymbol] { &self.acknowledged }
+
+    /// Symbols whose `MassCancel` was not acknowledged
+    /// (i.e., it failed to send, was rejected or timed out).
+    pub fn unacknowledged(&self) -> Vec<StockSymbol> {
+        self.unsent.iter()
+            .chain(self.rejected.iter())
+            .copied()
+            .chain(self.waiting.iter().map(|(_, symbol)| *symbol))
+            .collect()
+    }
+
+    /// Symbols whose `MassCancel` failed to send.
+    pub fn unsent(&self) -> &[StockSymbol] { &self.unsent }
+
+    /// Every error met while sending the requests or waiting for
+    /// their acknowledgements, in the order they occurred.
+    pub fn errors(&self) -> &[String] { &self.errors }
+
+    /// The `DisableOrderEntry` was acknowledged.
+    pub fn entry_disabled(&self) -> bool { self.entry_disabled }
+
+    /// Every request sent was acknowledged.
+    pub fn is_complete(&self) -> bool {
+        self.waiting.is_empty()
+            && self.rejected.is_empty()
+            && (self.entry_disabled || !self.disable_requested)
+    }
+
+    // Nothing more is expected from the server.
+    pub(crate) fn is_settled(&self) -> bool {
+        self.waiting.is_empty() && self.disabling.is_none()
+    }
+
+    // Record any acknowledgement (or rejection) of a request.
+    pub(crate) fn acknowledge(&mut self, response: &OuchResponse) {
+
+        use OuchResponse::*;
+        match response {
+
+            MassCancelResponse(ack) => {
+                if let Some(symbol) = self.take(ack.user_ref_num()) {
+                    self.acknowledged.push(symbol)
+                }
+            },
+
+            DisableOrderEntryResponse(ack)
+            if self.disabling == Some(ack.user_ref_num()) => {
+                self.disabling = None;
+                self.entry_disabled = true;
+            },
+
+            OrderRejected(rejected) => {
+                let num = rejected.user_ref_num();
+                if let Some(symbol) = self.take(num) {
+                    self.rejected.push(symbol)
+                }
+                if self.disabling == Some(num) {
+                    self.disabling = None;
+                }
+            },
+
+            _ => {},
+        }
+    }
+
+    // Record a request that failed to send, which will never be answered.
+    pub(crate) fn send_failed(
+        &mut self,
+        request: &OuchRequest,
+        error: &OuchError
+    ) {
+
+        #[cfg(feature = "logs")] {
+            log::error!("Kill switch failed to send {}: {}", request, error);
+        }
+
+        match request {
+            OuchRequest::MassCancel(cancel) => {
+                if let Some(symbol) = self.take(cancel.user_ref_num()) {
+                    self.unsent.push(symbol)
+                }
+            },
+            OuchRequest::DisableOrderEntry(disable)
+            if self.disabling == Some(disable.user_ref_num()) => {
+                self.disabling = None
+            },
+            _ => {},
+        }
+
+        self.errors.push(error.to_string())
+    }
+
+    // Record an error while waiting for acknowledgements,
+    // returning whether to keep waiting.
+    // Malformed packets are skipped, but anything else (e.g., a lost
+    // connection) means that no more acknowledgements can arrive.
+    pub(crate) fn recv_failed(&mut self, error: &OuchError) -> bool {
+
+        #[cfg(feature = "logs")] {
+            log::error!("Kill switch failed to receive: {}", error);
+        }
+
+        self.errors.push(error.to_string());
+        matches!(error,
+            OuchError::Parse(..) |
+            OuchError::UnknownResponse(..) |
+            OuchError::UnexpectedPacket(..)
+        )
+    }
+
+    fn take(&mut self, num: UserRefNum) -> Option<StockSymbol> {
+        let i = self.waiting.iter().position(|(n, _)| *n == num)?;
+        Some(self.waiting.remove(i).1)
+    }
+}
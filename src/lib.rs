@@ -1,19 +1,54 @@
 
 //! Simple OUCH v5.0 message library
 
-/// Contains an OuchClient type for sending and receiving messages.
+/// Contains OuchClient types for sending and receiving messages.
 pub mod client;
 pub use client::OuchClient;
+#[cfg(feature = "async")]
+pub use client::AsyncOuchClient;
+
+/// Contains OuchSupervisor types for reconnecting to the OUCH server.
+pub mod supervisor;
+pub use supervisor::OuchSupervisor;
+#[cfg(feature = "async")]
+pub use supervisor::AsyncOuchSupervisor;
+
+/// Contains an OrderManager for tracking the state of orders.
+pub mod orders;
+pub use orders::OrderManager;
+
+/// Contains a PositionTracker for positions and P&L from executions.
+pub mod positions;
+pub use positions::PositionTracker;
+
+/// Contains a RiskGate for pre-trade risk checks.
+pub mod risk;
+pub use risk::RiskGate;
+
+/// Contains a Throttle for limiting the rate of requests.
+pub mod throttle;
+pub use throttle::Throttle;
+
+/// Contains a KillSwitch for canceling everything in an incident.
+pub mod kill;
+pub use kill::KillSwitch;
 
 /// Contains OuchIn and OuchOut types for protocol-compliant messages.
 pub mod msg;
 
+/// Contains SoupBinTCP packet types that frame OUCH messages on the wire.
+pub mod soupbintcp;
+
+/// Contains codecs for framing OUCH messages with `tokio-util`.
+#[cfg(feature = "codec")]
+pub mod codec;
+
 /// Contains strong types used in OUCH protocol messages.
 pub mod types;
 
 /// Contains an OuchError type for recoverable errors.
 pub mod error;
 
-// Contains unit tests for the library (minus client)
+// Contains unit tests for the library
 #[cfg(test)] mod test;
 
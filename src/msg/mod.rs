@@ -13,7 +13,32 @@ pub use options::TagValue;
 
 
 macro_rules! define_msg {
+
+    // Only messages marked `#[optional_appendage]` may leave out
+    // the appendage (including its length marker) entirely.
+    (@optional_appendage optional_appendage) => { true };
+    (@optional_appendage) => { false };
+
+    // Messages without fields may still have options.
     (
+        $(#[$marker:ident])?
+        $msg_name:ident: $($msg_doc:expr;)?
+            options {
+                $( $opt_getter:ident: $opt_name:ident($opt_type:ty) ),*
+                $(,)?
+            }
+    ) => {
+        $crate::msg::define_msg!{
+            $(#[$marker])?
+            $msg_name: $($msg_doc;)?;
+                options {
+                    $( $opt_getter: $opt_name($opt_type) ),*
+                }
+        }
+    };
+
+    (
+        $(#[$marker:ident])?
         $msg_name:ident: $($msg_doc:expr;)?
             $(
                 $field_name:ident: $field_type:ident { 
@@ -21,6 +46,12 @@ macro_rules! define_msg {
                     $field_encoder:expr
                 }
             ),* $(,)?
+        $(;
+            options {
+                $( $opt_getter:ident: $opt_name:ident($opt_type:ty) ),*
+                $(,)?
+            }
+        )?
     ) => {
 
         #[derive(Debug, Clone, PartialEq, Eq)]
@@ -37,15 +68,31 @@ macro_rules! define_msg {
 
             // Data contains package without type tag, 
             // so all offsets should be one less than those in the spec.
-            pub(crate) fn parse(input: &[u8]) -> nom::IResult<&[u8], Self> {
+            // Errors name the field that failed, at its offset from `start`.
+            // Unknown options are refused if parsing is `strict`.
+            pub(crate) fn parse(
+                input: &[u8],
+                strict: bool,
+            ) -> Result<(&[u8], Self), $crate::error::ParseError> {
+
+                let start = input;
 
                 $(
                     let (input, $field_name): (&[u8], $field_type) 
-                        = $field_parser(input)?;
+                        = $field_parser(input).map_err(|e| {
+                            $crate::error::ParseError::from_nom(e, start, input)
+                                .with_field(stringify!($field_name))
+                        })?;
                 )*
 
                 let (input, opts) = 
-                    $crate::msg::options::OptionalAppendage::parse(input)?;
+                    $crate::msg::options::OptionalAppendage::parse(
+                        input,
+                        strict,
+                        $crate::msg::define_msg!(
+                            @optional_appendage $($marker)?
+                        )
+                    ).map_err(|e| e.within(start, input))?;
 
                 Ok((input, Self {
                     $( $field_name, )*
@@ -74,9 +121,108 @@ macro_rules! define_msg {
                 &self.optional_appendage.tag_values()
             }
 
+            $($(
+                #[doc = concat!(
+                    "Get the `", stringify!($opt_name), "` option, if present."
+                )]
+                pub fn $opt_getter(&self) -> Option<$opt_type> {
+                    self.options().iter().find_map(|option| match option {
+                        $crate::msg::options::TagValue::$opt_name(value)
+                            => Some(*value),
+                        _ => None,
+                    })
+                }
+            )*)?
+
+            // Only the options listed for the message may be added to it.
+            pub(crate) fn allows_option(
+                option: &$crate::msg::options::TagValue
+            ) -> bool {
+                false $($(
+                    || matches!(option,
+                        $crate::msg::options::TagValue::$opt_name(..)
+                    )
+                )*)?
+            }
+
+        }
+    }
+}
+
+// Responses can also be created from their fields (e.g., for a mock server).
+macro_rules! define_response {
+    (
+        $msg_name:ident: $($msg_doc:expr;)?
+            $(
+                $field_name:ident: $field_type:ident {
+                    $field_parser:expr,
+                    $field_encoder:expr
+                }
+            ),* $(,)?
+        $(;
+            options {
+                $( $opt_getter:ident: $opt_name:ident($opt_type:ty) ),*
+                $(,)?
+            }
+        )?
+    ) => {
+
+        $crate::msg::define_msg!{
+            $msg_name: $($msg_doc;)?
+                $(
+                    $field_name: $field_type {
+                        $field_parser,
+                        $field_encoder
+                    }
+                ),*
+            $(;
+                options {
+                    $( $opt_getter: $opt_name($opt_type) ),*
+                }
+            )?
+        }
+
+        impl $msg_name {
+
+            /// Create the message from its fields, with no options.
+            #[allow(clippy::too_many_arguments)]
+            pub fn new($( $field_name: $field_type, )*) -> Self {
+                Self {
+                    $( $field_name, )*
+                    optional_appendage:
+                        $crate::msg::options::OptionalAppendage::new()
+                }
+            }
+
+            /// Add a `TagValue` to the optional appendage.
+            /// Only the options listed for the message type are allowed,
+            /// along with `TagValue::Unknown` (e.g., to simulate a server
+            /// that sends options this crate does not know of yet),
+            /// as long as it does not use the tag of a known option,
+            /// so that the message always parses back to itself.
+            /// If a `TagValue` of the same variant already exists,
+            /// it will be overwritten in place by the new optional field.
+            pub fn add_option(
+                &mut self,
+                option: $crate::msg::options::TagValue
+            ) -> Result<(), $crate::error::OuchError> {
+
+                let unknown = matches!(option,
+                    $crate::msg::options::TagValue::Unknown { .. }
+                );
+                if !unknown && !Self::allows_option(&option) {
+                    return Err($crate::error::BadElementError::InvalidOption(
+                        stringify!($msg_name).to_string()
+                    ).into())
+                }
+
+                self.optional_appendage.add(option)?;
+                Ok(())
+            }
         }
     }
 }
 
-pub(self) use define_msg;
+use define_msg;
+use define_response;
 
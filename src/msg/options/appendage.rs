@@ -1,8 +1,13 @@
 
+use nom::number::streaming::be_u16;
+use nom::bytes::streaming::take;
+use nom::error::ErrorKind;
+
+use crate::error::{ ParseError, ParseErrorKind, BadElementError };
 use super::TagValue;
 
 /// Contains optional fields that may be included in a Request/Response.
-/// Only one instance of each variant of `TagValue` is allowed --
+/// Only one instance of each tag of `TagValue` is allowed --
 /// if another is added, the old one will be overwritten.
 #[derive(Debug, Clone, Eq)]
 pub struct OptionalAppendage {
@@ -24,17 +29,21 @@ impl OptionalAppendage {
 
     // NOTE: Does not check for validity of an option for each message type:
     // filter valid options within the message's `add_option` method.
-    pub(crate) fn add(&mut self, tag_value: TagValue) {
+    pub(crate) fn add(
+        &mut self,
+        tag_value: TagValue
+    ) -> Result<(), BadElementError> {
 
+        tag_value.check_unknown()?;
         let tvs = &mut self.tag_values;
 
-        use std::mem::discriminant as dd;
-        if let Some(i) = tvs.iter().position(|tv| dd(tv) == dd(&tag_value)) {
+        if let Some(i) = tvs.iter().position(|tv| tv.tag() == tag_value.tag()) {
             tvs[i] = tag_value
         } else {
             tvs.push(tag_value)
         }
 
+        Ok(())
     }
 
     pub(crate) fn encode(&self) -> Vec<u8> {
@@ -67,54 +76,84 @@ impl OptionalAppendage {
     #[allow(dead_code)]
     pub(crate) fn encode_nothing_if_empty(&self) -> Vec<u8> {
 
-        if self.tag_values.len() == 0 {
+        if self.tag_values.is_empty() {
             vec![]
         } else {
             self.encode()
         }
     }
 
-    pub(crate) fn parse(mut input: &[u8]) -> nom::IResult<&[u8], Self> {
+    // The appendage starts with its length, which must be followed by
+    // exactly that many bytes of options; the input after them is returned.
+    // Errors are at offsets from the start of the appendage (e.g., at the
+    // length marker of an option that does not fit within it).
+    // Unknown options are kept, unless parsing is `strict`.
+    // Only messages for which the appendage is `optional` may end before
+    // its length marker; otherwise the input is incomplete.
+    pub(crate) fn parse(
+        input: &[u8],
+        strict: bool,
+        optional: bool
+    ) -> Result<(&[u8], Self), ParseError> {
+
+        if optional && input.is_empty() {
+            return Ok((input, Self::new()))
+        }
+
+        let (rest, length) = be_u16(input).map_err(|e| {
+            ParseError::from_nom(e, input, input).with_field("options length")
+        })?;
+        let (rest, mut appendage) = take(length as usize)(rest)
+            .map_err(|e| {
+                ParseError::from_nom(e, input, rest).with_field("options")
+            })?;
 
+        // Offsets within the appendage, which ends before the `rest`.
+        let whole = &input[..input.len() - rest.len()];
         let mut tag_values: Vec<TagValue> = Vec::new();
 
-        // Ignore the first two bytes, they will contain the appendage length.
-        let mut tag_start = 2u8;
-
-        // Repeat this until the array is parsed:
-        while tag_start < input.len() as u8 {
-            // The first byte in the tag describes the length.
-            if let Some(tag_len) = input.get(tag_start as usize) {
-
-                // Use it to get the next X bytes (this one not included).
-                // Assumes the length of a tag is always a u8.
-                let tag_end = tag_start + *tag_len;
-                if tag_end < input.len() as u8 {
-
-                    let raw_tag_value = &input[
-                        ((tag_start as usize) + 1)..=(tag_end as usize)
-                    ];
-
-                    let (ipt, tag_value) = TagValue::parse(raw_tag_value)?;
-                    input = ipt;
-                    tag_values.push(tag_value);
-
-                    // The byte after (i.e., X + 1) will contain the length 
-                    // of the next tag value (if there is one).
-                    tag_start = tag_end as u8 + 1;
-
-                } else {
-                    return Err(
-                        nom::Err::Error(nom::error::Error::new(
-                            input, 
-                            nom::error::ErrorKind::Tag
-                        ))
-                    )
-                }
+        // Each option starts with its length, which counts its tag.
+        while let Some((&tag_len, after)) = appendage.split_first() {
+
+            let offset = whole.len() - appendage.len();
+            let invalid = |kind: ErrorKind| ParseError::new(
+                ParseErrorKind::Invalid(kind),
+                whole,
+                offset
+            );
+
+            let tag_len = tag_len as usize;
+            if tag_len == 0 || tag_len > after.len() {
+                return Err(invalid(ErrorKind::LengthValue)
+                    .with_field("options"))
+            }
+
+            let (raw_tag_value, next) = after.split_at(tag_len);
+            let field = TagValue::name(raw_tag_value[0]);
+
+            // The option is complete, so a short value is invalid.
+            let tag_value = match TagValue::parse(raw_tag_value) {
+                Ok(([], tag_value)) => tag_value,
+                Ok(..) => {
+                    return Err(invalid(ErrorKind::LengthValue)
+                        .with_field(field))
+                },
+                Err(e) => {
+                    return Err(ParseError::from_nom(e, whole, appendage)
+                        .complete()
+                        .with_field(field))
+                },
+            };
+
+            if strict && matches!(tag_value, TagValue::Unknown { .. }) {
+                return Err(invalid(ErrorKind::Tag).with_field(field))
             }
+
+            tag_values.push(tag_value);
+            appendage = next;
         }
 
-        Ok((input, Self{ tag_values }))
+        Ok((rest, Self{ tag_values }))
     }
 }
 
@@ -131,7 +170,7 @@ impl PartialEq for OptionalAppendage {
         // For every element in self, 
         // there should be an identical element somewhere in other.
         for stv in s_tvs.iter() {
-            if let None = o_tvs.iter().find(|otv| *otv == stv) {
+            if o_tvs.iter().find(|otv| *otv == stv).is_none() {
                 return false
             }
         }
@@ -38,12 +38,19 @@ macro_rules! tag_values {
     ) =>{
 
         /// An optional field on a message is communicated via TagValue.
-        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[derive(Debug, Clone, PartialEq, Eq)]
         pub enum TagValue {
             $(
                 $(#[doc = $doc])?
                 $name($typ),
             )*
+
+            /// An option with an unrecognized tag (e.g., one added to the
+            /// protocol after this version), kept so that it is encoded
+            /// exactly as it was received.
+            /// The value may be at most 254 bytes;
+            /// a longer value is refused when the option is added.
+            Unknown { tag: u8, bytes: Vec<u8> },
         }
 
         impl TagValue {
@@ -54,12 +61,15 @@ macro_rules! tag_values {
                     $(
                         Self::$name(val) => ($tag, $encoder(val).to_vec()),
                     )*
+                    Self::Unknown { tag, bytes } => (*tag, bytes.clone()),
                 };
 
                 // Start encoded array with length (calculated, not tracked);
                 // this is the start of the protocol's `TagValue` type.
                 // Safely assume that length will be less than u8::MAX,
-                // because each enum variant's inner type encodes to less.
+                // because each enum variant's inner type encodes to less
+                // (and unknown values are checked when they are added,
+                // or were read with a u8 length).
                 let length: u8 = encoded_value.len() as u8 + 1;
 
                 let mut data = vec![length, option_tag as u8];
@@ -71,7 +81,8 @@ macro_rules! tag_values {
             }
 
             // Length-marking byte of tag is not added to `data` when 
-            // `OptionalAppendage::parse` calls this method.
+            // `OptionalAppendage::parse` calls this method,
+            // so an unknown option takes the rest of the data as its value.
             pub(crate) fn parse(input: &[u8]) -> nom::IResult<&[u8], Self> {
 
                 let (input, opt) = nom::bytes::complete::take(1usize)(input)?;
@@ -82,17 +93,62 @@ macro_rules! tag_values {
                             (input, Self::$name(var))
                         },
                     )* 
-                    _ => return Err(
-                        nom::Err::Error(nom::error::Error::new(
-                            input, 
-                            nom::error::ErrorKind::Tag
-                        ))
+                    tag => (
+                        &input[input.len()..],
+                        Self::Unknown { tag, bytes: input.to_vec() }
                     ),
                 };
 
                 Ok(result)
             }
 
+            // Name the option with this tag (e.g., in a `ParseError`).
+            pub(crate) fn name(tag: u8) -> &'static str {
+                match tag {
+                    $(
+                        $tag => stringify!($name),
+                    )*
+                    _ => "Unknown",
+                }
+            }
+
+            // An unknown value must have a tag that this crate does not
+            // know, or it would be parsed back as (and replace) the known
+            // option with that tag.
+            // The length marker counts the tag, so an unknown value
+            // must also leave room for it within a u8.
+            pub(crate) fn check_unknown(
+                &self
+            ) -> Result<(), $crate::error::BadElementError> {
+                match self {
+                    Self::Unknown { tag, .. }
+                        if Self::name(*tag) != "Unknown" =>
+                    {
+                        Err($crate::error::BadElementError::KnownOptionTag {
+                            tag: *tag,
+                            name: Self::name(*tag).to_string(),
+                        })
+                    },
+                    Self::Unknown { tag, bytes } if bytes.len() > 254 => {
+                        Err($crate::error::BadElementError::OptionTooLong {
+                            tag: *tag,
+                            length: bytes.len(),
+                        })
+                    },
+                    _ => Ok(()),
+                }
+            }
+
+            // Options with the same tag replace each other in a message.
+            pub(crate) fn tag(&self) -> u8 {
+                match self {
+                    $(
+                        Self::$name(..) => $tag,
+                    )*
+                    Self::Unknown { tag, .. } => *tag,
+                }
+            }
+
         }
 
     }
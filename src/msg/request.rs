@@ -5,24 +5,32 @@ mod permission;
 mod query;
 mod modify;
 mod replace;
+mod validate;
 
 pub use self::{
     cancel::{ CancelOrder, MassCancel },
-    enter::EnterOrder,
+    enter::{ EnterOrder, EnterOrderBuilder },
     permission::{ EnableOrderEntry, DisableOrderEntry },
     query::AccountQuery,
     modify::ModifyOrder,
     replace::ReplaceOrder,
 };
 
+use crate::error::BadElementError;
+
 macro_rules! ouch_requests {
     ($([$tag:expr] $msg_kind:ident),*$(,)?) => {
 
         /// Client requests that can be sent via OUCH.
-        /// All requests may be repeated benignly. 
-        /// This gives the client the ability to re-send any request if it is 
-        /// uncertain whether NASDAQ received it in the case of a connection 
-        /// loss or an application error.
+        /// A request repeated with the same UserRefNum is not acted on twice,
+        /// so it may be re-sent unchanged if it is uncertain whether NASDAQ
+        /// received it (e.g., after a connection loss or an application
+        /// error). Sent again with a new UserRefNum, it is a new request
+        /// (e.g., an `EnterOrder` becomes a second order).
+        ///
+        /// The supervisors never re-send requests: after reconnecting, only
+        /// the server's responses are replayed, and it is up to the caller
+        /// to check them before repeating a request that failed to send.
         ///
         /// All inbound messages on an OUCH port are processed sequentially. 
         /// This guarantees that if two orders are entered consecutively on 
@@ -35,6 +43,14 @@ macro_rules! ouch_requests {
             )*
         }
 
+        /// The type of an `OuchRequest`, without its data.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum OuchRequestKind {
+            $(
+                $msg_kind,
+            )*
+        }
+
         impl OuchRequest {
 
             /// Encode message to bytes.
@@ -51,6 +67,62 @@ macro_rules! ouch_requests {
                 }
             }
 
+            /// Parse a request message (including its type tag) from bytes
+            /// (e.g., to simulate the server or to audit outbound traffic).
+            /// Unlike responses, an unrecognized type tag is an error.
+            /// Options with unrecognized tags are kept as `TagValue::Unknown`.
+            pub fn parse(
+                input: &[u8]
+            ) -> Result<(&[u8], Self), $crate::error::ParseError> {
+                Self::parse_with(input, false)
+            }
+
+            /// Parse a request message like `parse`, but refuse any option
+            /// with an unrecognized tag (e.g., for conformance testing).
+            pub fn parse_strict(
+                input: &[u8]
+            ) -> Result<(&[u8], Self), $crate::error::ParseError> {
+                Self::parse_with(input, true)
+            }
+
+            fn parse_with(
+                input: &[u8],
+                strict: bool,
+            ) -> Result<(&[u8], Self), $crate::error::ParseError> {
+
+                use $crate::error::{ ParseError, ParseErrorKind };
+
+                let (body, msg_tag) =
+                    nom::bytes::streaming::take(1usize)(input).map_err(|e| {
+                        ParseError::from_nom(e, input, input)
+                    })?;
+
+                match msg_tag[0] {
+                    $(
+                        $tag => {
+                            let (rest, msg) = $msg_kind::parse(body, strict)
+                                .map_err(|e| e.in_message($tag, input))?;
+                            Ok((rest, Self::$msg_kind(msg)))
+                        },
+                    )*
+
+                    _ => Err(ParseError::new(
+                        ParseErrorKind::Invalid(nom::error::ErrorKind::Tag),
+                        input,
+                        0
+                    ).with_field("message type")),
+                }
+            }
+
+            /// Get the type of the request.
+            pub fn kind(&self) -> OuchRequestKind {
+                match self {
+                    $(
+                        Self::$msg_kind(..) => OuchRequestKind::$msg_kind,
+                    )*
+                }
+            }
+
             /// Get the options attached to this request.
             pub fn options(&self) -> &Vec<$crate::msg::options::TagValue> {
                 match self {
@@ -103,3 +175,18 @@ ouch_requests!{
     [b'M'] ModifyOrder,
 }
 
+impl OuchRequest {
+
+    /// Check that the fields and options of the request are consistent,
+    /// catching the structural errors that the exchange would reject
+    /// (e.g., a `GoodTilTime` order without an `ExpireTime`).
+    /// Only orders, replacements and modifications need checking.
+    pub fn validate(&self) -> Result<(), BadElementError> {
+        match self {
+            Self::EnterOrder(msg) => msg.validate(),
+            Self::ReplaceOrder(msg) => msg.validate(),
+            Self::ModifyOrder(msg) => msg.validate(),
+            _ => Ok(()),
+        }
+    }
+}
@@ -41,7 +41,10 @@ define_msg!{
         user_ref_num: UserRefNum
             { UserRefNum::parse, UserRefNum::encode },
         quantity: u32
-            { be_u32, |i: &u32| u32::to_be_bytes(*i) },
+            { be_u32, |i: &u32| u32::to_be_bytes(*i) };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
 impl CancelOrder {
@@ -92,17 +95,13 @@ impl CancelOrder {
     ) -> Result<(), BadElementError> {
 
         // Filter out unacceptable TagValue types.
-        match option {
-            TagValue::UserRefIndex(..) => { /* Continue */ },
-
-            _ => {
-                return Err(BadElementError::InvalidOption(
-                    "CancelOrder".to_string()
-                ))
-            },
+        if !Self::allows_option(&option) {
+            return Err(BadElementError::InvalidOption(
+                "CancelOrder".to_string()
+            ))
         }
 
-        Ok(self.optional_appendage.add(option))
+        self.optional_appendage.add(option)
     }
 }
 
@@ -147,7 +146,12 @@ define_msg!{
         firm: Mpid
             { Mpid::parse, Mpid::encode },
         symbol: StockSymbol
-            { StockSymbol::parse, StockSymbol::encode },
+            { StockSymbol::parse, StockSymbol::encode };
+    options {
+        group_id: GroupId(u16),
+        side: Side(Side),
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
 impl MassCancel {
@@ -172,6 +176,7 @@ impl MassCancel {
     /// - GroupId
     /// - UserRefIndex
     /// - Side
+    ///
     /// For `GroupId`: if the value is set to 0, 
     /// all orders without a Group ID will be canceled.
     /// If the value is not specified, all orders, regardless
@@ -182,21 +187,13 @@ impl MassCancel {
     ) -> Result<(), BadElementError> {
 
         // Filter out unacceptable TagValue types.
-        use TagValue::*;
-        match option {
-            GroupId(..) |
-            Side(..) |
-            UserRefIndex(..) 
-                => { /* Continue */ },
-
-            _ => {
-                return Err(BadElementError::InvalidOption(
-                    "MassCancel".to_string()
-                ))
-            },
+        if !Self::allows_option(&option) {
+            return Err(BadElementError::InvalidOption(
+                "MassCancel".to_string()
+            ))
         }
 
-        Ok(self.optional_appendage.add(option))
+        self.optional_appendage.add(option)
     }
 }
 
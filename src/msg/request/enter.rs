@@ -3,7 +3,19 @@ use nom::number::streaming::be_u32;
 
 use crate::error::BadElementError;
 use crate::{ types::*, msg::define_msg };
-use crate::msg::options::{ TagValue, OptionalAppendage };
+use crate::msg::{
+    OuchRequest,
+    options::{ TagValue, OptionalAppendage },
+};
+
+use super::validate::{
+    check_quantity,
+    check_expire_time,
+    check_handle_inst,
+    check_reserves,
+    check_peg_offsets,
+    check_locate,
+};
 
 
 /// Create an EnterOrder request message.
@@ -97,14 +109,42 @@ define_msg!{
         cross_type: CrossType
             { CrossType::parse, CrossType::encode },
         order_token: OrderToken
-            { OrderToken::parse, OrderToken::encode },
+            { OrderToken::parse, OrderToken::encode };
+    options {
+        firm: Firm(Mpid),
+        min_qty: MinQty(u32),
+        retail: Retail(Ternary),
+        max_floor: MaxFloor(u32),
+        price_type: PriceType(PriceType),
+        peg_offset: PegOffset(SignedPrice),
+        discretion_price: DiscretionPrice(Price),
+        discretion_price_type: DiscretionPriceType(PriceType),
+        discretion_peg_offset: DiscretionPegOffset(SignedPrice),
+        post_only: PostOnly(bool),
+        random_reserves: RandomReserves(u32),
+        expire_time: ExpireTime(ElapsedTime),
+        trade_now: TradeNow(Ternary),
+        handle_inst: HandleInst(HandleInst),
+        group_id: GroupId(u16),
+        shares_located: SharesLocated(bool),
+        locate_broker: LocateBroker(Mpid),
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
 impl EnterOrder {
 
+    /// Start building an Enter order, with defaults for everything
+    /// except its UserRefNum, side, quantity, symbol and price.
+    /// See `EnterOrderBuilder`.
+    pub fn builder() -> EnterOrderBuilder {
+        EnterOrderBuilder::new()
+    }
+
     /// Create a new Enter order.
     /// For `quantity`, entering over 1,000,000 (maximum shares per order) 
     /// results in an error.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         user_ref_num: UserRefNum,
         side: Side,
@@ -141,6 +181,7 @@ impl EnterOrder {
 
     /// WARN: Panics!
     /// This constructor will panic if quantity >= 1,000,000.
+    #[allow(clippy::too_many_arguments)]
     pub fn assert_new(
         user_ref_num: UserRefNum,
         side: Side,
@@ -197,36 +238,251 @@ impl EnterOrder {
     ) -> Result<(), BadElementError> {
 
         // Filter out unacceptable TagValue types.
-        use TagValue::*;
-        match option {
-            Firm(..) |
-            MinQty(..) |
-            Retail(..) |
-            MaxFloor(..) |
-            PriceType(..) |
-            PegOffset(..) |
-            DiscretionPrice(..) |
-            DiscretionPriceType(..) |
-            DiscretionPegOffset(..) |
-            PostOnly(..) |
-            RandomReserves(..) |
-            ExpireTime(..) |
-            TradeNow(..) |
-            HandleInst(..) |
-            GroupId(..) |
-            SharesLocated(..) |
-            LocateBroker(..) |
-            UserRefIndex(..) 
-                => { /* Continue */ },
-
-            _ => {
-                return Err(BadElementError::InvalidOption(
-                    "EnterOrder".to_string()
-                ))
-            },
+        if !Self::allows_option(&option) {
+            return Err(BadElementError::InvalidOption(
+                "EnterOrder".to_string()
+            ))
         }
 
-        Ok(self.optional_appendage.add(option))
+        self.optional_appendage.add(option)
+    }
+
+    /// Check that the fields and options of the order are consistent,
+    /// catching the structural errors that the exchange would reject.
+    pub fn validate(&self) -> Result<(), BadElementError> {
+
+        let options = self.options();
+
+        check_quantity(self.quantity())?;
+        check_expire_time(self.time_in_force(), options)?;
+        check_handle_inst(self.cross_type(), options)?;
+        check_reserves(self.quantity(), options)?;
+        check_peg_offsets(options)?;
+        check_locate(self.side(), options)
     }
 }
 
+
+/// Builds an `EnterOrder` request with typed setters for its options.
+/// ```
+/// use slouch::{ msg::EnterOrder, types::* };
+///
+/// let request = EnterOrder::builder()
+///     .user_ref_num(UserRefNum::new())
+///     .side(Side::Buy)
+///     .quantity(500)
+///     .symbol(StockSymbol::from("STONKS").unwrap())
+///     .price(Price::new(35000).unwrap())
+///     .max_floor(100)
+///     .post_only()
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// UserRefNum, side, quantity, symbol and price are required.
+/// Otherwise, the order defaults to `TimeInForce::Day`, `Display::Visible`,
+/// `Capacity::Agency`, `CrossType::ContinuousMarket`, no intermarket sweep
+/// and a blank OrderToken.
+#[derive(Debug, Clone)]
+pub struct EnterOrderBuilder {
+    user_ref_num: Option<UserRefNum>,
+    side: Option<Side>,
+    quantity: Option<u32>,
+    symbol: Option<StockSymbol>,
+    price: Option<Price>,
+    time_in_force: TimeInForce,
+    display: Display,
+    capacity: Capacity,
+    intermarket_sweep: bool,
+    cross_type: CrossType,
+    order_token: OrderToken,
+    options: Vec<TagValue>,
+}
+
+impl EnterOrderBuilder {
+
+    fn new() -> Self {
+        EnterOrderBuilder {
+            user_ref_num: None,
+            side: None,
+            quantity: None,
+            symbol: None,
+            price: None,
+            time_in_force: TimeInForce::Day,
+            display: Display::Visible,
+            capacity: Capacity::Agency,
+            intermarket_sweep: false,
+            cross_type: CrossType::ContinuousMarket,
+            order_token: OrderToken::from("")
+                .expect("Blank OrderToken is valid"),
+            options: Vec::new(),
+        }
+    }
+
+    /// Set the UserRefNum of the order (required).
+    pub fn user_ref_num(mut self, user_ref_num: UserRefNum) -> Self {
+        self.user_ref_num = Some(user_ref_num);
+        self
+    }
+
+    /// Set the side of the order (required).
+    pub fn side(mut self, side: Side) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    /// Set the number of shares (required).
+    pub fn quantity(mut self, quantity: u32) -> Self {
+        self.quantity = Some(quantity);
+        self
+    }
+
+    /// Set the symbol of the order (required).
+    pub fn symbol(mut self, symbol: StockSymbol) -> Self {
+        self.symbol = Some(symbol);
+        self
+    }
+
+    /// Set the price of the order (required).
+    pub fn price(mut self, price: Price) -> Self {
+        self.price = Some(price);
+        self
+    }
+
+    /// Set the time in force. Defaults to `TimeInForce::Day`.
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    /// Set the display. Defaults to `Display::Visible`.
+    pub fn display(mut self, display: Display) -> Self {
+        self.display = display;
+        self
+    }
+
+    /// Set the capacity. Defaults to `Capacity::Agency`.
+    pub fn capacity(mut self, capacity: Capacity) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Mark the order as an intermarket sweep.
+    pub fn intermarket_sweep(mut self) -> Self {
+        self.intermarket_sweep = true;
+        self
+    }
+
+    /// Set the cross type. Defaults to `CrossType::ContinuousMarket`.
+    pub fn cross_type(mut self, cross_type: CrossType) -> Self {
+        self.cross_type = cross_type;
+        self
+    }
+
+    /// Set the OrderToken. Defaults to blank.
+    pub fn order_token(mut self, order_token: OrderToken) -> Self {
+        self.order_token = order_token;
+        self
+    }
+
+    /// Enter the order on behalf of this firm.
+    pub fn firm(self, firm: Mpid) -> Self {
+        self.option(TagValue::Firm(firm))
+    }
+
+    /// Only execute at least this many shares at once (a round lot).
+    pub fn min_qty(self, min_qty: u32) -> Self {
+        self.option(TagValue::MinQty(min_qty))
+    }
+
+    /// Display only this many shares, keeping the rest in reserve.
+    pub fn max_floor(self, max_floor: u32) -> Self {
+        self.option(TagValue::MaxFloor(max_floor))
+    }
+
+    /// Replenish the displayed shares with a random number of up to
+    /// this many shares.
+    pub fn random_reserves(self, shares: u32) -> Self {
+        self.option(TagValue::RandomReserves(shares))
+    }
+
+    /// Make the order post-only (it will not execute on entry).
+    pub fn post_only(self) -> Self {
+        self.option(TagValue::PostOnly(true))
+    }
+
+    /// Set how the price is interpreted (e.g., `PriceType::MidpointPeg`).
+    pub fn price_type(self, price_type: PriceType) -> Self {
+        self.option(TagValue::PriceType(price_type))
+    }
+
+    /// Peg the order, offset from the pegged price.
+    pub fn peg(self, price_type: PriceType, offset: SignedPrice) -> Self {
+        self.option(TagValue::PriceType(price_type))
+            .option(TagValue::PegOffset(offset))
+    }
+
+    /// Allow the order to execute at a discretionary price.
+    pub fn discretion(self, price: Price, price_type: PriceType) -> Self {
+        self.option(TagValue::DiscretionPrice(price))
+            .option(TagValue::DiscretionPriceType(price_type))
+    }
+
+    /// Offset the discretionary price from its pegged price.
+    pub fn discretion_offset(self, offset: SignedPrice) -> Self {
+        self.option(TagValue::DiscretionPegOffset(offset))
+    }
+
+    /// Make the order Good 'Til Time, expiring after this duration.
+    pub fn expire_after(mut self, duration: ElapsedTime) -> Self {
+        self.time_in_force = TimeInForce::GoodTilTime;
+        self.option(TagValue::ExpireTime(duration))
+    }
+
+    /// Set the handling instructions, which must suit the cross type.
+    pub fn handle_inst(self, handle_inst: HandleInst) -> Self {
+        self.option(TagValue::HandleInst(handle_inst))
+    }
+
+    /// Locate the shares of a short sale with this broker.
+    pub fn locate(self, broker: Mpid) -> Self {
+        self.option(TagValue::LocateBroker(broker))
+    }
+
+    /// Add any other `TagValue` (see `EnterOrder::add_option`).
+    /// If one of the same variant was already set, it is replaced.
+    pub fn option(mut self, option: TagValue) -> Self {
+        self.options.push(option);
+        self
+    }
+
+    /// Create the request, checking that the required fields are set
+    /// and that the order is consistent (see `EnterOrder::validate`).
+    pub fn build(self) -> Result<OuchRequest, BadElementError> {
+
+        let mut order = EnterOrder::new(
+            required(self.user_ref_num, "user_ref_num")?,
+            required(self.side, "side")?,
+            required(self.quantity, "quantity")?,
+            required(self.symbol, "symbol")?,
+            required(self.price, "price")?,
+            self.time_in_force,
+            self.display,
+            self.capacity,
+            self.intermarket_sweep,
+            self.cross_type,
+            self.order_token,
+        )?;
+
+        for option in self.options {
+            order.add_option(option)?;
+        }
+
+        order.validate()?;
+        Ok(OuchRequest::EnterOrder(order))
+    }
+}
+
+fn required<T>(field: Option<T>, name: &str) -> Result<T, BadElementError> {
+    field.ok_or_else(|| BadElementError::MissingField(name.to_string()))
+}
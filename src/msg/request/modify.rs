@@ -5,6 +5,8 @@ use crate::error::BadElementError;
 use crate::{ types::*, msg::define_msg };
 use crate::msg::options::{ TagValue, OptionalAppendage };
 
+use super::validate::{ check_quantity, check_locate };
+
 
 /// Create a ModifyOrder request message.
 /// WARN: PANIC! This constructor will PANIC if quantity >= 1,000,000.
@@ -38,6 +40,7 @@ macro_rules! modify {
 }
 
 define_msg!{
+    #[optional_appendage]
     ModifyOrder: 
     "Modify values for an existing order, without affecting priority.";
         user_ref_num: UserRefNum
@@ -45,7 +48,12 @@ define_msg!{
         side: Side
             { Side::parse, Side::encode },
         quantity: u32
-            { be_u32, |i: &u32| u32::to_be_bytes(*i) },
+            { be_u32, |i: &u32| u32::to_be_bytes(*i) };
+    options {
+        shares_located: SharesLocated(bool),
+        locate_broker: LocateBroker(Mpid),
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
 impl ModifyOrder {
@@ -103,20 +111,18 @@ impl ModifyOrder {
     ) -> Result<(), BadElementError> {
 
         // Filter out unacceptable TagValue types.
-        use TagValue::*;
-        match option {
-            SharesLocated(..) |
-            LocateBroker(..) |
-            UserRefIndex(..) => { /* Continue */ },
-
-            _ => {
-                return Err(BadElementError::InvalidOption(
-                    "ModifyOrder".to_string()
-                ))
-            },
+        if !Self::allows_option(&option) {
+            return Err(BadElementError::InvalidOption(
+                "ModifyOrder".to_string()
+            ))
         }
 
-        Ok(self.optional_appendage.add(option))
+        self.optional_appendage.add(option)
     }
-} 
 
+    /// Check that the fields and options of the modification are consistent.
+    pub fn validate(&self) -> Result<(), BadElementError> {
+        check_quantity(self.quantity())?;
+        check_locate(self.side(), self.options())
+    }
+}
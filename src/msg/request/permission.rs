@@ -39,7 +39,10 @@ define_msg!{
         user_ref_num: UserRefNum
             { UserRefNum::parse, UserRefNum::encode },
         firm: Mpid
-            { Mpid::parse, Mpid::encode },
+            { Mpid::parse, Mpid::encode };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
 impl DisableOrderEntry {
@@ -66,17 +69,13 @@ impl DisableOrderEntry {
     ) -> Result<(), BadElementError> {
 
         // Filter out unacceptable TagValue types.
-        match option {
-            TagValue::UserRefIndex(..) => { /* Continue */ },
-
-            _ => {
-                return Err(BadElementError::InvalidOption(
-                    "DisableOrderEntry".to_string()
-                ))
-            },
+        if !Self::allows_option(&option) {
+            return Err(BadElementError::InvalidOption(
+                "DisableOrderEntry".to_string()
+            ))
         }
 
-        Ok(self.optional_appendage.add(option))
+        self.optional_appendage.add(option)
     }
 }
 
@@ -116,7 +115,10 @@ define_msg!{
         user_ref_num: UserRefNum
             { UserRefNum::parse, UserRefNum::encode },
         firm: Mpid
-            { Mpid::parse, Mpid::encode },
+            { Mpid::parse, Mpid::encode };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
 impl EnableOrderEntry {
@@ -143,17 +145,13 @@ impl EnableOrderEntry {
     ) -> Result<(), BadElementError> {
 
         // Filter out unacceptable TagValue types.
-        match option {
-            TagValue::UserRefIndex(..) => { /* Continue */ },
-
-            _ => {
-                return Err(BadElementError::InvalidOption(
-                    "EnableOrderEntry".to_string()
-                ))
-            },
+        if !Self::allows_option(&option) {
+            return Err(BadElementError::InvalidOption(
+                "EnableOrderEntry".to_string()
+            ))
         }
 
-        Ok(self.optional_appendage.add(option))
+        self.optional_appendage.add(option)
     }
 }
 
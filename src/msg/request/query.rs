@@ -22,8 +22,12 @@ macro_rules! account_query {
 }
 
 define_msg!{
+    #[optional_appendage]
     AccountQuery: 
     "Use when recovering state to request the next available UserRefNum.";
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
 impl AccountQuery {
@@ -44,17 +48,19 @@ impl AccountQuery {
     ) -> Result<(), BadElementError> {
 
         // Filter out unacceptable TagValue types.
-        match option {
-            TagValue::UserRefIndex(..) => { /* Continue */ },
-
-            _ => {
-                return Err(BadElementError::InvalidOption(
-                    "AccountQuery".to_string()
-                ))
-            },
+        if !Self::allows_option(&option) {
+            return Err(BadElementError::InvalidOption(
+                "AccountQuery".to_string()
+            ))
         }
 
-        Ok(self.optional_appendage.add(option))
+        self.optional_appendage.add(option)
     }
 } 
 
+impl Default for AccountQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
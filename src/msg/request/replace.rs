@@ -5,6 +5,14 @@ use crate::error::BadElementError;
 use crate::{ types::*, msg::define_msg };
 use crate::msg::options::{ TagValue, OptionalAppendage };
 
+use super::validate::{
+    check_quantity,
+    check_expire_time,
+    check_reserves,
+    check_peg_offsets,
+    check_locate,
+};
+
 
 /// Create a ReplaceOrder request message.
 /// WARN: PANIC! This constructor will PANIC if quantity >= 1,000,000.
@@ -91,7 +99,25 @@ define_msg!{
         intermarket_sweep: bool
             { nsdq_util::parse_bool, |v: &bool| nsdq_util::encode_bool(*v) },
         order_token: OrderToken
-            { OrderToken::parse, OrderToken::encode },
+            { OrderToken::parse, OrderToken::encode };
+    options {
+        min_qty: MinQty(u32),
+        max_floor: MaxFloor(u32),
+        price_type: PriceType(PriceType),
+        peg_offset: PegOffset(SignedPrice),
+        discretion_price: DiscretionPrice(Price),
+        discretion_price_type: DiscretionPriceType(PriceType),
+        discretion_peg_offset: DiscretionPegOffset(SignedPrice),
+        post_only: PostOnly(bool),
+        random_reserves: RandomReserves(u32),
+        expire_time: ExpireTime(ElapsedTime),
+        trade_now: TradeNow(Ternary),
+        handle_inst: HandleInst(HandleInst),
+        side: Side(Side),
+        shares_located: SharesLocated(bool),
+        locate_broker: LocateBroker(Mpid),
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
 impl ReplaceOrder {
@@ -103,6 +129,7 @@ impl ReplaceOrder {
     ///
     /// If a `TagValue::UserRefIndex` option is used on the original order, 
     /// it must also be added to this request. (See `add_option` below.)
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         old_ref_num: UserRefNum,
         new_ref_num: UserRefNum,
@@ -133,6 +160,7 @@ impl ReplaceOrder {
 
     /// WARN: Panics!
     /// This constructor will panic if quantity >= 1,000,000.
+    #[allow(clippy::too_many_arguments)]
     pub fn assert_new(
         old_ref_num: UserRefNum,
         new_ref_num: UserRefNum,
@@ -180,6 +208,7 @@ impl ReplaceOrder {
     /// - LocateBroker
     /// - UserRefIndex
     /// - Side
+    ///
     /// NOTE: If a `UserRefIndex` option is used on the original order, 
     /// it MUST also be added here.
     pub fn add_option(
@@ -188,33 +217,30 @@ impl ReplaceOrder {
     ) -> Result<(), BadElementError> {
 
         // Filter out unacceptable TagValue types.
-        use TagValue::*;
-        match option {
-            MinQty(..) |
-            MaxFloor(..) |
-            PriceType(..) |
-            PegOffset(..) |
-            DiscretionPrice(..) |
-            DiscretionPriceType(..) |
-            DiscretionPegOffset(..) |
-            PostOnly(..) |
-            RandomReserves(..) |
-            ExpireTime(..) |
-            TradeNow(..) |
-            HandleInst(..) |
-            Side(..) |
-            SharesLocated(..) |
-            LocateBroker(..) |
-            UserRefIndex(..) => { /* Continue */ },
-
-            _ => {
-                return Err(BadElementError::InvalidOption(
-                    "ReplaceOrder".to_string()
-                ))
-            },
+        if !Self::allows_option(&option) {
+            return Err(BadElementError::InvalidOption(
+                "ReplaceOrder".to_string()
+            ))
         }
 
-        Ok(self.optional_appendage.add(option))
+        self.optional_appendage.add(option)
     }
-}
 
+    /// Check that the fields and options of the replacement are consistent.
+    /// The cross type and side are inherited from the original order,
+    /// so they are only checked if a `Side` option is given.
+    pub fn validate(&self) -> Result<(), BadElementError> {
+
+        let options = self.options();
+
+        check_quantity(self.quantity())?;
+        check_expire_time(self.time_in_force(), options)?;
+        check_reserves(self.quantity(), options)?;
+        check_peg_offsets(options)?;
+
+        match self.side() {
+            Some(side) => check_locate(side, options),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,165 @@
+
+use crate::error::BadElementError;
+use crate::types::*;
+use crate::msg::options::TagValue;
+
+
+// Cross-field checks shared by the requests that carry order details.
+
+// Orders may be entered for up to 999,999 shares.
+const MAX_QUANTITY: u32 = 1_000_000;
+
+// MinQty must be a multiple of a round lot.
+const ROUND_LOT: u32 = 100;
+
+
+pub(super) fn check_quantity(quantity: u32) -> Result<(), BadElementError> {
+    match quantity < MAX_QUANTITY {
+        true => Ok(()),
+        false => Err(BadElementError::InvalidQuantity(quantity)),
+    }
+}
+
+pub(super) fn check_expire_time(
+    time_in_force: TimeInForce,
+    options: &[TagValue],
+) -> Result<(), BadElementError> {
+
+    let has_expire_time = options.iter()
+        .any(|option| matches!(option, TagValue::ExpireTime(..)));
+
+    match (time_in_force, has_expire_time) {
+        (TimeInForce::GoodTilTime, false) => {
+            Err(BadElementError::MissingExpireTime)
+        },
+        _ => Ok(()),
+    }
+}
+
+// Each handling instruction only applies to certain crosses.
+pub(super) fn check_handle_inst(
+    cross_type: CrossType,
+    options: &[TagValue],
+) -> Result<(), BadElementError> {
+
+    let Some(handle_inst) = options.iter().find_map(|option| match option {
+        TagValue::HandleInst(handle_inst) => Some(*handle_inst),
+        _ => None,
+    }) else {
+        return Ok(())
+    };
+
+    use HandleInst::*;
+    let valid = match handle_inst {
+        ImbalanceOnly => matches!(
+            cross_type,
+            CrossType::Opening | CrossType::Closing
+        ),
+        RetailOrder1 | RetailOrder2 | RetailPriceImprovement => {
+            cross_type == CrossType::Retail
+        },
+        ExtendedContinuous => cross_type == CrossType::ExtendedLife,
+        DirectListingCapitalRaise => cross_type == CrossType::Halt,
+        NoInstructions => true,
+    };
+
+    match valid {
+        true => Ok(()),
+        false => Err(BadElementError::InvalidHandleInst(
+            handle_inst,
+            cross_type
+        )),
+    }
+}
+
+// The displayed and minimum quantities must fit the order.
+pub(super) fn check_reserves(
+    quantity: u32,
+    options: &[TagValue],
+) -> Result<(), BadElementError> {
+
+    for option in options {
+        match *option {
+
+            TagValue::MaxFloor(max_floor) if max_floor > quantity => {
+                return Err(BadElementError::InvalidMaxFloor {
+                    max_floor,
+                    quantity,
+                })
+            },
+
+            TagValue::MinQty(min_qty) if min_qty % ROUND_LOT != 0 => {
+                return Err(BadElementError::InvalidMinQty(min_qty))
+            },
+
+            _ => {},
+        }
+    }
+
+    Ok(())
+}
+
+// Offsets only apply to pegged prices (the price type defaults to limit).
+pub(super) fn check_peg_offsets(
+    options: &[TagValue]
+) -> Result<(), BadElementError> {
+
+    let mut pegged = false;
+    let mut discretion_pegged = false;
+    let mut offset = None;
+    let mut discretion_offset = None;
+
+    for option in options {
+        match option {
+            TagValue::PriceType(price_type) => {
+                pegged = is_pegged(*price_type)
+            },
+            TagValue::DiscretionPriceType(price_type) => {
+                discretion_pegged = is_pegged(*price_type)
+            },
+            TagValue::PegOffset(..) => offset = Some("PegOffset"),
+            TagValue::DiscretionPegOffset(..) => {
+                discretion_offset = Some("DiscretionPegOffset")
+            },
+            _ => {},
+        }
+    }
+
+    if let Some(name) = offset
+    && !pegged {
+        return Err(BadElementError::UnpeggedOffset(name.to_string()))
+    }
+    if let Some(name) = discretion_offset
+    && !discretion_pegged {
+        return Err(BadElementError::UnpeggedOffset(name.to_string()))
+    }
+
+    Ok(())
+}
+
+fn is_pegged(price_type: PriceType) -> bool {
+
+    use PriceType::*;
+    matches!(price_type, MarketPeg | MidpointPeg | PrimaryPeg | MarketMakerPeg)
+}
+
+// Short sales (unless exempt) must have located the shares.
+pub(super) fn check_locate(
+    side: Side,
+    options: &[TagValue],
+) -> Result<(), BadElementError> {
+
+    if side != Side::SellShort {
+        return Ok(())
+    }
+
+    let located = options.iter().any(|option| matches!(
+        option,
+        TagValue::SharesLocated(true) | TagValue::LocateBroker(..)
+    ));
+
+    match located {
+        true => Ok(()),
+        false => Err(BadElementError::MissingLocate),
+    }
+}
@@ -37,6 +37,15 @@ pub use self::{
     modified::OrderModified,
 };
 
+use crate::types::{
+    UserRefNum,
+    NaiveTime,
+    OrderToken,
+    StockSymbol,
+    Side,
+    Price,
+};
+
 
 macro_rules! ouch_responses {
     ($([$tag:expr] $msg_name:ident),*$(,)?) => {
@@ -51,28 +60,108 @@ macro_rules! ouch_responses {
             Unknown(char, Vec<u8>),
         }
 
+        /// The type of an `OuchResponse`, without its data
+        /// (e.g., to route a response or to count responses by type).
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum OuchResponseKind {
+            $(
+                $msg_name,
+            )*
+            Unknown,
+        }
+
         impl OuchResponse {
 
-            /// 
-            pub fn parse(input: &[u8]) -> nom::IResult<&[u8], Self> {
-                
-                let (input, msg_tag) = 
-                    nom::bytes::complete::take(1usize)(input)?;
+            /// Parse a response message (including its type tag) from bytes.
+            /// On failure, the `ParseError` describes the message and field
+            /// that could not be parsed, and whether more data is needed.
+            /// Options with unrecognized tags are kept as `TagValue::Unknown`.
+            pub fn parse(
+                input: &[u8]
+            ) -> Result<(&[u8], Self), $crate::error::ParseError> {
+                Self::parse_with(input, false)
+            }
+
+            /// Parse a response message like `parse`, but refuse any option
+            /// with an unrecognized tag (e.g., for conformance testing).
+            pub fn parse_strict(
+                input: &[u8]
+            ) -> Result<(&[u8], Self), $crate::error::ParseError> {
+                Self::parse_with(input, true)
+            }
+
+            fn parse_with(
+                input: &[u8],
+                strict: bool,
+            ) -> Result<(&[u8], Self), $crate::error::ParseError> {
+
+                let (body, msg_tag) =
+                    nom::bytes::streaming::take(1usize)(input).map_err(|e| {
+                        $crate::error::ParseError::from_nom(e, input, input)
+                    })?;
 
                 match msg_tag[0] {
                     $(
                         $tag => {
-                            let (input, msg) = $msg_name::parse(input)?;
-                            Ok((input, Self::$msg_name(msg)))
+                            let (rest, msg) = $msg_name::parse(body, strict)
+                                .map_err(|e| e.in_message($tag, input))?;
+                            Ok((rest, Self::$msg_name(msg)))
                         },
                     )*
 
+                    // The length of an unknown message is not known,
+                    // so it takes the rest of the input.
                     typ => Ok((
-                        input, 
-                        Self::Unknown(typ as char, input.to_vec())
+                        &body[body.len()..],
+                        Self::Unknown(typ as char, body.to_vec())
                     )),
                 }
             }
+
+            /// Encode message (including its type tag) to bytes,
+            /// as the server would send it.
+            pub fn encode(&self) -> Vec<u8> {
+
+                match self {
+                    $(
+                        Self::$msg_name(msg) => {
+                            let mut bytes = vec![$tag];
+                            bytes.extend(msg.encode());
+                            bytes
+                        },
+                    )*
+
+                    Self::Unknown(typ, data) => {
+                        let mut bytes = vec![*typ as u8];
+                        bytes.extend(data);
+                        bytes
+                    },
+                }
+            }
+
+            /// Get the type of the response.
+            pub fn kind(&self) -> OuchResponseKind {
+                match self {
+                    $(
+                        Self::$msg_name(..) => OuchResponseKind::$msg_name,
+                    )*
+                    Self::Unknown(..) => OuchResponseKind::Unknown,
+                }
+            }
+        }
+
+        impl std::fmt::Display for OuchResponse {
+            /// Write the type tag of the response only (no data).
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let ch = match self {
+                    $(
+                        Self::$msg_name(..) => $tag as char,
+                    )*
+                    Self::Unknown(typ, ..) => *typ,
+                };
+
+                ch.fmt(f)
+            }
         }
 
     }
@@ -98,4 +187,138 @@ ouch_responses! {
     [b'X'] MassCancelResponse,
 }
 
+// Fields shared by several responses, so that callers need not match on
+// every variant (e.g., to route a response to the handler of its order).
+impl OuchResponse {
+
+    /// Get the UserRefNum of the order (or other request) the response
+    /// refers to. For `OrderReplaced`, this is the new UserRefNum.
+    pub fn user_ref_num(&self) -> Option<UserRefNum> {
 
+        use OuchResponse::*;
+        match self {
+            OrderPriorityUpdate(msg) => Some(msg.user_ref_num()),
+            OrderRestated(msg) => Some(msg.user_ref_num()),
+            BrokenTrade(msg) => Some(msg.user_ref_num()),
+            DisableOrderEntryResponse(msg) => Some(msg.user_ref_num()),
+            EnableOrderEntryResponse(msg) => Some(msg.user_ref_num()),
+            OrderExecuted(msg) => Some(msg.user_ref_num()),
+            OrderAccepted(msg) => Some(msg.user_ref_num()),
+            OrderModified(msg) => Some(msg.user_ref_num()),
+            OrderCanceled(msg) => Some(msg.user_ref_num()),
+            OrderRejected(msg) => Some(msg.user_ref_num()),
+            OrderReplaced(msg) => Some(msg.new_ref_num()),
+            AiqCanceled(msg) => Some(msg.user_ref_num()),
+            CancelPending(msg) => Some(msg.user_ref_num()),
+            CancelRejected(msg) => Some(msg.user_ref_num()),
+            MassCancelResponse(msg) => Some(msg.user_ref_num()),
+            SystemEvent(..) | AccountQueryResponse(..) | Unknown(..) => None,
+        }
+    }
+
+    /// Get the time the response was sent (every response but `Unknown`).
+    pub fn timestamp(&self) -> Option<NaiveTime> {
+
+        use OuchResponse::*;
+        match self {
+            SystemEvent(msg) => Some(msg.timestamp()),
+            AccountQueryResponse(msg) => Some(msg.timestamp()),
+            OrderPriorityUpdate(msg) => Some(msg.timestamp()),
+            OrderRestated(msg) => Some(msg.timestamp()),
+            BrokenTrade(msg) => Some(msg.timestamp()),
+            DisableOrderEntryResponse(msg) => Some(msg.timestamp()),
+            EnableOrderEntryResponse(msg) => Some(msg.timestamp()),
+            OrderExecuted(msg) => Some(msg.timestamp()),
+            OrderAccepted(msg) => Some(msg.timestamp()),
+            OrderModified(msg) => Some(msg.timestamp()),
+            OrderCanceled(msg) => Some(msg.timestamp()),
+            OrderRejected(msg) => Some(msg.timestamp()),
+            OrderReplaced(msg) => Some(msg.timestamp()),
+            AiqCanceled(msg) => Some(msg.timestamp()),
+            CancelPending(msg) => Some(msg.timestamp()),
+            CancelRejected(msg) => Some(msg.timestamp()),
+            MassCancelResponse(msg) => Some(msg.timestamp()),
+            Unknown(..) => None,
+        }
+    }
+
+    /// Get the OrderToken, if the response carries one.
+    pub fn order_token(&self) -> Option<OrderToken> {
+
+        use OuchResponse::*;
+        match self {
+            OrderAccepted(msg) => Some(msg.order_token()),
+            OrderReplaced(msg) => Some(msg.order_token()),
+            OrderRejected(msg) => Some(msg.order_token()),
+            BrokenTrade(msg) => Some(msg.order_token()),
+            _ => None,
+        }
+    }
+
+    /// Get the symbol, if the response carries one.
+    pub fn symbol(&self) -> Option<StockSymbol> {
+
+        use OuchResponse::*;
+        match self {
+            OrderAccepted(msg) => Some(msg.symbol()),
+            OrderReplaced(msg) => Some(msg.symbol()),
+            MassCancelResponse(msg) => Some(msg.symbol()),
+            _ => None,
+        }
+    }
+
+    /// Get the side, if the response carries one.
+    pub fn side(&self) -> Option<Side> {
+
+        use OuchResponse::*;
+        match self {
+            OrderAccepted(msg) => Some(msg.side()),
+            OrderReplaced(msg) => Some(msg.side()),
+            OrderModified(msg) => Some(msg.side()),
+            _ => None,
+        }
+    }
+
+    /// Get the quantity, if the response carries one.
+    /// Its meaning depends on the response: the shares of an accepted,
+    /// replaced or modified order, the shares executed,
+    /// or the shares decremented by a cancel.
+    pub fn quantity(&self) -> Option<u32> {
+
+        use OuchResponse::*;
+        match self {
+            OrderAccepted(msg) => Some(msg.quantity()),
+            OrderReplaced(msg) => Some(msg.quantity()),
+            OrderModified(msg) => Some(msg.quantity()),
+            OrderExecuted(msg) => Some(msg.quantity()),
+            OrderCanceled(msg) => Some(msg.quantity()),
+            _ => None,
+        }
+    }
+
+    /// Get the price, if the response carries one
+    /// (for `OrderExecuted`, this is the execution price).
+    pub fn price(&self) -> Option<Price> {
+
+        use OuchResponse::*;
+        match self {
+            OrderAccepted(msg) => Some(msg.price()),
+            OrderReplaced(msg) => Some(msg.price()),
+            OrderExecuted(msg) => Some(msg.price()),
+            OrderPriorityUpdate(msg) => Some(msg.price()),
+            AiqCanceled(msg) => Some(msg.price()),
+            _ => None,
+        }
+    }
+
+    /// Get the match number of an execution or broken trade.
+    pub fn match_number(&self) -> Option<u64> {
+
+        use OuchResponse::*;
+        match self {
+            OrderExecuted(msg) => Some(msg.match_number()),
+            BrokenTrade(msg) => Some(msg.match_number()),
+            _ => None,
+        }
+    }
+}
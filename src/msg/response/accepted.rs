@@ -12,10 +12,18 @@ use crate::types::{
     Capacity,
     CrossType,
     OrderState,
-    OrderToken
+    OrderToken,
+    Mpid,
+    Ternary,
+    PriceType,
+    SignedPrice,
+    RouteId,
+    ElapsedTime,
+    HandleInst,
+    BboWeight,
 };
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     OrderAccepted:
     "Acknowledges the receipt and acceptance of a valid EnterOrder request. \
@@ -55,6 +63,29 @@ crate::msg::define_msg!{
         order_state: OrderState
             { OrderState::parse, OrderState::encode },
         order_token: OrderToken
-            { OrderToken::parse, OrderToken::encode },
+            { OrderToken::parse, OrderToken::encode };
+    options {
+        secondary_ord_ref_num: SecondaryOrdRefNum(u64),
+        firm: Firm(Mpid),
+        min_qty: MinQty(u32),
+        retail: Retail(Ternary),
+        max_floor: MaxFloor(u32),
+        price_type: PriceType(PriceType),
+        peg_offset: PegOffset(SignedPrice),
+        discretion_price: DiscretionPrice(Price),
+        discretion_price_type: DiscretionPriceType(PriceType),
+        discretion_peg_offset: DiscretionPegOffset(SignedPrice),
+        post_only: PostOnly(bool),
+        random_reserves: RandomReserves(u32),
+        route: Route(RouteId),
+        expire_time: ExpireTime(ElapsedTime),
+        trade_now: TradeNow(Ternary),
+        handle_inst: HandleInst(HandleInst),
+        bbo_weight: BboWeight(BboWeight),
+        group_id: GroupId(u16),
+        shares_located: SharesLocated(bool),
+        locate_broker: LocateBroker(Mpid),
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
@@ -8,7 +8,7 @@ use crate::types::{
     OrderToken,
 };
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     BrokenTrade: 
     "Informs you that an execution has been broken. \
@@ -26,6 +26,9 @@ crate::msg::define_msg!{
         reason: BrokenReason
             { BrokenReason::parse, BrokenReason::encode },
         order_token: OrderToken
-            { OrderToken::parse, OrderToken::encode },
+            { OrderToken::parse, OrderToken::encode };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
@@ -13,7 +13,7 @@ use crate::types::{
 };
 
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
     
     OrderCanceled: 
     "Informs you that an order has been reduced or canceled. \n \
@@ -28,13 +28,17 @@ crate::msg::define_msg!{
         quantity: u32 
             { be_u32, |i: &u32| u32::to_be_bytes(*i) },
         reason: CancelReason
-            { CancelReason::parse, CancelReason::encode },
+            { CancelReason::parse, CancelReason::encode };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     AiqCanceled:
-    "";
+    "Informs you that an order was reduced or canceled by \
+    Anti-Internalization Qualifier (AIQ) self-match prevention.";
         timestamp: NaiveTime { 
             nsdq_util::parse_ouch_time_bold, 
             |v: &NaiveTime| nsdq_util::encode_ouch_time(*v) 
@@ -52,10 +56,13 @@ crate::msg::define_msg!{
         liquidity: Liquidity
             { Liquidity::parse, Liquidity::encode },
         aiq_strategy: AiqStrategy
-            { AiqStrategy::parse, AiqStrategy::encode },
+            { AiqStrategy::parse, AiqStrategy::encode };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     CancelPending:
     "Sent in response to a CancelOrder request for a cross order during a \
@@ -68,10 +75,13 @@ crate::msg::define_msg!{
             |v: &NaiveTime| nsdq_util::encode_ouch_time(*v) 
         },
         user_ref_num: UserRefNum
-            { UserRefNum::parse, UserRefNum::encode },
+            { UserRefNum::parse, UserRefNum::encode };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     CancelRejected:
     "Sent in response to a partial cancel request (with non-zero “quantity”) \
@@ -86,10 +96,13 @@ crate::msg::define_msg!{
             |v: &NaiveTime| nsdq_util::encode_ouch_time(*v) 
         },
         user_ref_num: UserRefNum
-            { UserRefNum::parse, UserRefNum::encode },
+            { UserRefNum::parse, UserRefNum::encode };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     MassCancelResponse:
     "Acknowledges the receipt of a valid MassCancel request. \
@@ -103,6 +116,10 @@ crate::msg::define_msg!{
         firm: Mpid
             { Mpid::parse, Mpid::encode },
         symbol: StockSymbol
-            { StockSymbol::parse, StockSymbol::encode },
+            { StockSymbol::parse, StockSymbol::encode };
+    options {
+        group_id: GroupId(u16),
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
@@ -1,6 +1,8 @@
 
+use crate::error::ParseError;
 use crate::types::{ NaiveTime, EventCode };
 
+/// Signals a system-wide event, such as the start or end of the day.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SystemEvent {
     timestamp: NaiveTime,
@@ -10,12 +12,28 @@ pub struct SystemEvent {
 #[allow(dead_code)]
 impl SystemEvent {
 
+    /// Create the message from its fields.
+    pub fn new(timestamp: NaiveTime, event_code: EventCode) -> Self {
+        Self { timestamp, event_code }
+    }
+
     // Data contains package without type tag, 
     // so all offsets should be one less than those in the spec.
-    pub(crate) fn parse(input: &[u8]) -> nom::IResult<&[u8], Self> {
-
-        let (input, timestamp) = nsdq_util::parse_ouch_time_bold(input)?;
-        let (input, event_code) = EventCode::parse(input)?;
+    // There is no optional appendage, so parsing is always strict.
+    pub(crate) fn parse(
+        input: &[u8],
+        _strict: bool
+    ) -> Result<(&[u8], Self), ParseError> {
+
+        let start = input;
+        let (input, timestamp) = nsdq_util::parse_ouch_time_bold(input)
+            .map_err(|e| {
+                ParseError::from_nom(e, start, input).with_field("timestamp")
+            })?;
+        let (input, event_code) = EventCode::parse(input)
+            .map_err(|e| {
+                ParseError::from_nom(e, start, input).with_field("event_code")
+            })?;
 
         Ok((input, Self { timestamp, event_code }))
     }
@@ -23,6 +41,7 @@ impl SystemEvent {
     pub(crate) fn encode(&self) -> Vec<u8> {
         
         let mut bytes: Vec<u8> = Vec::with_capacity(9usize);
+        bytes.extend(nsdq_util::encode_ouch_time(self.timestamp));
         bytes.extend(self.event_code.encode());
 
         bytes
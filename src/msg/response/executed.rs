@@ -8,7 +8,7 @@ use crate::types::{
     Liquidity 
 };
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     OrderExecuted: 
     "Informs you that part or all of an order has been executed.";
@@ -25,6 +25,9 @@ crate::msg::define_msg!{
         liquidity: Liquidity
             { Liquidity::parse, Liquidity::encode },
         match_number: u64
-            { be_u64, |i: &u64| u64::to_be_bytes(*i) },
+            { be_u64, |i: &u64| u64::to_be_bytes(*i) };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
@@ -3,7 +3,7 @@ use nom::number::streaming::be_u32;
 
 use crate::types::{ UserRefNum, Side, NaiveTime };
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     OrderModified: "Sent in response to a ModifyOrder request.";
         timestamp: NaiveTime { 
@@ -15,6 +15,9 @@ crate::msg::define_msg!{
         side: Side
             { Side::parse, Side::encode },
         quantity: u32
-            { be_u32, |i: &u32| u32::to_be_bytes(*i) },
+            { be_u32, |i: &u32| u32::to_be_bytes(*i) };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
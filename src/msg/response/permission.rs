@@ -1,7 +1,7 @@
 
 use crate::types::{ NaiveTime, Mpid, UserRefNum };
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     DisableOrderEntryResponse:
     "Acknowledges the receipt of the DisableOrderEntry request. \
@@ -13,10 +13,13 @@ crate::msg::define_msg!{
         user_ref_num: UserRefNum
             { UserRefNum::parse, UserRefNum::encode },
         firm: Mpid 
-            { Mpid::parse, Mpid::encode },
+            { Mpid::parse, Mpid::encode };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     EnableOrderEntryResponse:
     "Acknowledges the receipt of the EnableOrderEntry request. \
@@ -28,6 +31,9 @@ crate::msg::define_msg!{
         user_ref_num: UserRefNum
             { UserRefNum::parse, UserRefNum::encode },
         firm: Mpid 
-            { Mpid::parse, Mpid::encode },
+            { Mpid::parse, Mpid::encode };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
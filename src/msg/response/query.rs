@@ -1,7 +1,7 @@
 
 use crate::types::{ NaiveTime, UserRefNum };
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     AccountQueryResponse: "Indicates the next available UserRefNum.";
         timestamp: NaiveTime { 
@@ -9,6 +9,9 @@ crate::msg::define_msg!{
             |v: &NaiveTime| nsdq_util::encode_ouch_time(*v) 
         },
         next_user_ref_num: UserRefNum
-            { UserRefNum::parse, UserRefNum::encode }
+            { UserRefNum::parse, UserRefNum::encode };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
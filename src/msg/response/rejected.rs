@@ -6,7 +6,7 @@ use crate::types::{
     OrderToken
 };
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     OrderRejected:
     "May be sent in response to an EnterOrder or ReplaceOrder request if the \
@@ -21,6 +21,9 @@ crate::msg::define_msg!{
         reason: RejectReason
             { RejectReason::parse, RejectReason::encode },
         order_token: OrderToken
-            { OrderToken::parse, OrderToken::encode },
+            { OrderToken::parse, OrderToken::encode };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
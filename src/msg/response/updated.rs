@@ -9,7 +9,7 @@ use crate::types::{
     Price, 
 };
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     OrderPriorityUpdate: 
     "Sent whenever priority of the order has been changed by the system.";
@@ -24,10 +24,13 @@ crate::msg::define_msg!{
         display: Display
             { Display::parse, Display::encode },
         order_ref_num: u64
-            { be_u64, |i: &u64| u64::to_be_bytes(*i) },
+            { be_u64, |i: &u64| u64::to_be_bytes(*i) };
+    options {
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
-crate::msg::define_msg!{
+crate::msg::define_response!{
 
     OrderRestated: 
     "Sent when the system modifies an order as part of its order management.";
@@ -38,6 +41,12 @@ crate::msg::define_msg!{
         user_ref_num: UserRefNum 
             { UserRefNum::parse, UserRefNum::encode },
         reason: RestateReason
-            { RestateReason::parse, RestateReason::encode },
+            { RestateReason::parse, RestateReason::encode };
+    options {
+        secondary_ord_ref_num: SecondaryOrdRefNum(u64),
+        display_quantity: DisplayQuantity(u32),
+        display_price: DisplayPrice(Price),
+        user_ref_index: UserRefIndex(u8),
+    }
 }
 
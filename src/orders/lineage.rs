@@ -0,0 +1,85 @@
+
+use crate::types::Price;
+
+use super::{ Order, Fill, OrderStatus };
+
+
+/// Replace history of a single logical order,
+/// from the order first entered through each of its replacements.
+///
+/// Replacements that were rejected, or accepted as dead
+/// (because the order was no longer live), are kept in the history.
+#[derive(Debug, Clone)]
+pub struct Lineage<'a> {
+    orders: Vec<&'a Order>,
+}
+
+impl<'a> Lineage<'a> {
+
+    // Orders must be listed in the order they were entered.
+    pub(crate) fn new(orders: Vec<&'a Order>) -> Option<Self> {
+        match orders.is_empty() {
+            true => None,
+            false => Some(Lineage { orders }),
+        }
+    }
+
+    /// The first order of the lineage, which strategies know it by.
+    pub fn root(&self) -> &'a Order {
+        self.orders[0]
+    }
+
+    /// The newest order of the lineage that was not rejected.
+    pub fn current(&self) -> &'a Order {
+        self.orders.iter()
+            .rev()
+            .find(|order| order.status() != OrderStatus::Rejected)
+            .unwrap_or(&self.orders[0])
+    }
+
+    /// Every order of the lineage, in the order they were entered.
+    pub fn history(&self) -> &[&'a Order] {
+        &self.orders
+    }
+
+    /// Executions against every order of the lineage.
+    pub fn fills(&self) -> impl Iterator<Item = &'a Fill> + '_ {
+        self.orders.iter().flat_map(|order| order.fills())
+    }
+
+    /// Number of shares that remain open on the book.
+    /// Pending replacements are not counted until they are accepted.
+    pub fn leaves_quantity(&self) -> u32 {
+        self.orders.iter()
+            .filter(|order| order.status() != OrderStatus::Pending)
+            .map(|order| order.leaves_quantity())
+            .sum()
+    }
+
+    /// Number of shares executed across the lineage,
+    /// less any broken trades.
+    pub fn cumulative_quantity(&self) -> u32 {
+        self.fills().map(|fill| fill.quantity()).sum()
+    }
+
+    /// Volume-weighted average price of the executions across the lineage
+    /// (rounded down), or `None` if nothing has been executed.
+    pub fn average_price(&self) -> Option<Price> {
+
+        let quantity = self.cumulative_quantity() as u128;
+        if quantity == 0 {
+            return None
+        }
+
+        let notional: u128 = self.fills()
+            .map(|fill| fill.quantity() as u128 * fill.price().val() as u128)
+            .sum();
+
+        Price::new((notional / quantity) as u64).ok()
+    }
+
+    /// Some order of the lineage may still be executed.
+    pub fn is_open(&self) -> bool {
+        self.orders.iter().any(|order| order.status().is_open())
+    }
+}
@@ -0,0 +1,472 @@
+
+/// Contains the state of a single order.
+mod order;
+
+/// Contains the replace history of a logical order.
+mod lineage;
+
+pub use self::{
+    order::{ Order, OrderStatus, Fill },
+    lineage::Lineage,
+};
+
+use std::collections::HashMap;
+
+use crate::{
+    msg::{
+        OuchRequest,
+        OuchResponse,
+        EnterOrder,
+        ReplaceOrder,
+        OrderAccepted,
+        OrderReplaced,
+    },
+    types::{ UserRefNum, OrderToken, StockSymbol, OrderState },
+};
+
+
+/// Keeps the state of every order entered in a session,
+/// built up from each request sent and each response received.
+///
+/// Pass every `OuchRequest` sent to `track_request`
+/// and every `OuchResponse` received to `track_response`.
+/// Orders first seen in a response (e.g., entered before a restart)
+/// are tracked from their `OrderAccepted` onward.
+///
+/// Replacements are linked to the orders they replace,
+/// so that any UserRefNum can be resolved to its `Lineage`.
+#[derive(Debug, Clone, Default)]
+pub struct OrderManager {
+    orders: HashMap<UserRefNum, Order>,
+    // Orders are listed in the order they were first seen.
+    entered: Vec<UserRefNum>,
+    by_token: HashMap<[u8; 14], Vec<UserRefNum>>,
+    by_symbol: HashMap<[u8; 8], Vec<UserRefNum>>,
+    replaces: HashMap<UserRefNum, UserRefNum>,
+    replaced_by: HashMap<UserRefNum, Vec<UserRefNum>>,
+}
+
+impl OrderManager {
+
+    /// Create an empty order manager.
+    pub fn new() -> Self {
+        OrderManager::default()
+    }
+
+    /// Get the order with this UserRefNum.
+    pub fn get(&self, user_ref_num: UserRefNum) -> Option<&Order> {
+        self.orders.get(&user_ref_num)
+    }
+
+    /// Every order, in the order they were first seen.
+    pub fn orders(&self) -> impl Iterator<Item = &Order> {
+        self.entered.iter().filter_map(|num| self.orders.get(num))
+    }
+
+    /// Every order that may still be executed.
+    pub fn open_orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders().filter(|order| order.status().is_open())
+    }
+
+    /// Orders with this OrderToken.
+    /// OrderTokens are not required to be unique, so there may be several.
+    pub fn by_token(
+        &self,
+        token: &OrderToken
+    ) -> impl Iterator<Item = &Order> {
+        self.lookup(self.by_token.get(&token.encode()))
+    }
+
+    /// Orders for this symbol.
+    pub fn by_symbol(
+        &self,
+        symbol: &StockSymbol
+    ) -> impl Iterator<Item = &Order> {
+        self.lookup(self.by_symbol.get(&symbol.encode()))
+    }
+
+    /// Resolve a UserRefNum to the first order of its replace chain.
+    pub fn root(&self, user_ref_num: UserRefNum) -> UserRefNum {
+
+        let mut num = user_ref_num;
+        while let Some(old) = self.replaces.get(&num) {
+            num = *old;
+        }
+
+        num
+    }
+
+    /// Get the replace history of the logical order
+    /// that any UserRefNum in its replace chain belongs to.
+    pub fn lineage(&self, user_ref_num: UserRefNum) -> Option<Lineage<'_>> {
+
+        let mut nums = vec![self.root(user_ref_num)];
+        let mut i = 0;
+        while let Some(num) = nums.get(i) {
+            if let Some(new) = self.replaced_by.get(num) {
+                nums.extend(new);
+            }
+            i += 1;
+        }
+
+        // Replacements are entered after the orders they replace.
+        nums.sort_by_key(|num| num.val());
+
+        Lineage::new(nums.iter()
+            .filter_map(|num| self.orders.get(num))
+            .collect()
+        )
+    }
+
+    fn lookup<'a>(
+        &'a self,
+        nums: Option<&'a Vec<UserRefNum>>
+    ) -> impl Iterator<Item = &'a Order> {
+        nums.into_iter()
+            .flatten()
+            .filter_map(|num| self.orders.get(num))
+    }
+
+    /// Update the manager with a request sent to the server.
+    /// Returns the order created by the request, if any.
+    ///
+    /// New orders (and replacements) are tracked as pending.
+    /// Other requests have no effect until the server responds.
+    pub fn track_request(&mut self, request: &OuchRequest) -> Option<&Order> {
+
+        use OuchRequest::*;
+        match request {
+            EnterOrder(enter) => self.entered(enter),
+            ReplaceOrder(replace) => {
+                self.link(replace.old_ref_num(), replace.new_ref_num());
+                self.replacing(replace)
+            },
+            _ => None,
+        }
+    }
+
+    /// Undo `track_request` for a request that was never sent
+    /// (e.g., because its send failed or was cancelled).
+    /// Orders that the server has already responded to are kept.
+    pub fn untrack_request(&mut self, request: &OuchRequest) {
+
+        use OuchRequest::*;
+        match request {
+            EnterOrder(enter) => self.remove_pending(enter.user_ref_num()),
+            ReplaceOrder(replace) => {
+                let new = replace.new_ref_num();
+                if self.orders.get(&new)
+                    .is_some_and(|order| order.status != OrderStatus::Pending)
+                {
+                    return
+                }
+
+                self.unlink(replace.old_ref_num(), new);
+                self.remove_pending(new);
+            },
+            _ => {},
+        }
+    }
+
+    /// Update the manager with a response received from the server.
+    /// Returns the order affected by the response, if it is tracked.
+    pub fn track_response(
+        &mut self,
+        response: &OuchResponse
+    ) -> Option<&Order> {
+
+        use OuchResponse::*;
+        match response {
+
+            OrderAccepted(accepted) => Some(self.accepted(accepted)),
+
+            OrderReplaced(replaced) => {
+                self.link(replaced.old_ref_num(), replaced.new_ref_num());
+                Some(self.replaced(replaced))
+            },
+
+            OrderExecuted(executed) => {
+                let order = self.orders.get_mut(&executed.user_ref_num())?;
+
+                // Executions are only counted once.
+                let match_number = executed.match_number();
+                if order.fills().iter()
+                    .all(|fill| fill.match_number() != match_number)
+                {
+                    order.execute(Fill::new(
+                        match_number,
+                        executed.quantity(),
+                        executed.price(),
+                        executed.liquidity(),
+                    ));
+                }
+
+                Some(order)
+            },
+
+            OrderCanceled(canceled) => {
+                let order = self.orders.get_mut(&canceled.user_ref_num())?;
+                order.set_leaves_quantity(
+                    order.leaves_quantity.saturating_sub(canceled.quantity())
+                );
+                Some(order)
+            },
+
+            AiqCanceled(canceled) => {
+                let order = self.orders.get_mut(&canceled.user_ref_num())?;
+                order.set_leaves_quantity(order.leaves_quantity
+                    .saturating_sub(canceled.decrement_shares())
+                );
+                Some(order)
+            },
+
+            OrderModified(modified) => {
+                let order = self.orders.get_mut(&modified.user_ref_num())?;
+                order.side = modified.side();
+                order.set_leaves_quantity(modified.quantity());
+                Some(order)
+            },
+
+            OrderRejected(rejected) => {
+                let order = self.orders.get_mut(&rejected.user_ref_num())?;
+                order.leaves_quantity = 0;
+                order.status = OrderStatus::Rejected;
+                Some(order)
+            },
+
+            BrokenTrade(broken) => {
+                let order = self.orders.get_mut(&broken.user_ref_num())?;
+                order.bust(broken.match_number());
+                Some(order)
+            },
+
+            OrderPriorityUpdate(update) => {
+                let order = self.orders.get_mut(&update.user_ref_num())?;
+                order.price = update.price();
+                order.display = update.display();
+                order.order_ref_num = Some(update.order_ref_num());
+                Some(order)
+            },
+
+            _ => None,
+        }
+    }
+
+    // Record that an order replaces another.
+    // A replacement is accepted as a new order (and may be dead)
+    // when the original order is no longer live, so it is linked
+    // as soon as the request is sent.
+    fn link(&mut self, old: UserRefNum, new: UserRefNum) {
+
+        if self.replaces.contains_key(&new) || self.root(old) == new {
+            return
+        }
+
+        self.replaces.insert(new, old);
+        self.replaced_by.entry(old).or_default().push(new);
+    }
+
+    fn unlink(&mut self, old: UserRefNum, new: UserRefNum) {
+
+        if self.replaces.get(&new) != Some(&old) {
+            return
+        }
+
+        self.replaces.remove(&new);
+        if let Some(replacements) = self.replaced_by.get_mut(&old) {
+            replacements.retain(|num| *num != new);
+            if replacements.is_empty() {
+                self.replaced_by.remove(&old);
+            }
+        }
+    }
+
+    fn entered(&mut self, enter: &EnterOrder) -> Option<&Order> {
+
+        // A repeated request (with the same UserRefNum) is not a new order.
+        let num = enter.user_ref_num();
+        if self.orders.contains_key(&num) {
+            return None
+        }
+
+        Some(self.insert(Order {
+            user_ref_num: num,
+            order_token: enter.order_token(),
+            symbol: enter.symbol(),
+            side: enter.side(),
+            price: enter.price(),
+            time_in_force: enter.time_in_force(),
+            display: enter.display(),
+            quantity: enter.quantity(),
+            leaves_quantity: enter.quantity(),
+            order_ref_num: None,
+            status: OrderStatus::Pending,
+            fills: Vec::new(),
+        }))
+    }
+
+    // The replacement inherits the symbol and side of the original order,
+    // so it cannot be tracked until the server responds
+    // if the original order is unknown.
+    fn replacing(&mut self, replace: &ReplaceOrder) -> Option<&Order> {
+
+        let num = replace.new_ref_num();
+        if self.orders.contains_key(&num) {
+            return None
+        }
+
+        let old = self.orders.get(&replace.old_ref_num())?;
+
+        let order = Order {
+            user_ref_num: num,
+            order_token: replace.order_token(),
+            symbol: old.symbol,
+            side: old.side,
+            price: replace.price(),
+            time_in_force: replace.time_in_force(),
+            display: replace.display(),
+            quantity: replace.quantity(),
+            leaves_quantity: replace.quantity(),
+            order_ref_num: None,
+            status: OrderStatus::Pending,
+            fills: Vec::new(),
+        };
+
+        Some(self.insert(order))
+    }
+
+    fn accepted(&mut self, accepted: &OrderAccepted) -> &Order {
+
+        let num = accepted.user_ref_num();
+
+        // Accepted values may differ from those entered.
+        let order = Order {
+            user_ref_num: num,
+            order_token: accepted.order_token(),
+            symbol: accepted.symbol(),
+            side: accepted.side(),
+            price: accepted.price(),
+            time_in_force: accepted.time_in_force(),
+            display: accepted.display(),
+            quantity: accepted.quantity(),
+            leaves_quantity: accepted.quantity(),
+            order_ref_num: Some(accepted.order_ref_num()),
+            status: OrderStatus::Pending,
+            fills: Vec::new(),
+        };
+
+        self.open(order, accepted.order_state())
+    }
+
+    fn replaced(&mut self, replaced: &OrderReplaced) -> &Order {
+
+        if let Some(old) = self.orders.get_mut(&replaced.old_ref_num()) {
+            old.kill();
+        }
+
+        let order = Order {
+            user_ref_num: replaced.new_ref_num(),
+            order_token: replaced.order_token(),
+            symbol: replaced.symbol(),
+            side: replaced.side(),
+            price: replaced.price(),
+            time_in_force: replaced.time_in_force(),
+            display: replaced.display(),
+            quantity: replaced.quantity(),
+            leaves_quantity: replaced.quantity(),
+            order_ref_num: Some(replaced.order_ref_num()),
+            status: OrderStatus::Pending,
+            fills: Vec::new(),
+        };
+
+        self.open(order, replaced.order_state())
+    }
+
+    // An order was accepted by the server, either live or dead.
+    // Orders that were already accepted are left as they are.
+    fn open(&mut self, order: Order, state: OrderState) -> &Order {
+
+        let num = order.user_ref_num;
+        let pending = self.orders.get(&num)
+            .map(|order| order.status == OrderStatus::Pending);
+
+        match pending {
+            Some(true) => self.reindex(order),
+            Some(false) => return &self.orders[&num],
+            None => { self.insert(order); },
+        }
+
+        let order = self.orders.get_mut(&num)
+            .expect("Order was just inserted");
+
+        match state {
+            OrderState::Live => order.set_leaves_quantity(order.quantity),
+            OrderState::Dead => order.kill(),
+        }
+
+        order
+    }
+
+    // Forget an order that the server has not responded to.
+    fn remove_pending(&mut self, num: UserRefNum) {
+
+        let pending = self.orders.get(&num)
+            .is_some_and(|order| order.status == OrderStatus::Pending);
+        if !pending {
+            return
+        }
+
+        let order = self.orders.remove(&num).expect("Order was just found");
+        self.entered.retain(|entered| *entered != num);
+        unindex(&mut self.by_token, order.order_token.encode(), num);
+        unindex(&mut self.by_symbol, order.symbol.encode(), num);
+    }
+
+    // Replace a pending order with the values accepted by the server,
+    // whose token and symbol may differ from those entered.
+    fn reindex(&mut self, order: Order) {
+
+        let num = order.user_ref_num;
+        let Some(old) = self.orders.insert(num, order) else { return };
+        let new = &self.orders[&num];
+
+        if old.order_token != new.order_token {
+            let token = new.order_token.encode();
+            unindex(&mut self.by_token, old.order_token.encode(), num);
+            self.by_token.entry(token).or_default().push(num);
+        }
+        if old.symbol != new.symbol {
+            let symbol = new.symbol.encode();
+            unindex(&mut self.by_symbol, old.symbol.encode(), num);
+            self.by_symbol.entry(symbol).or_default().push(num);
+        }
+    }
+
+    fn insert(&mut self, order: Order) -> &Order {
+
+        let num = order.user_ref_num;
+
+        self.entered.push(num);
+        self.by_token.entry(order.order_token.encode())
+            .or_default()
+            .push(num);
+        self.by_symbol.entry(order.symbol.encode())
+            .or_default()
+            .push(num);
+
+        self.orders.entry(num).or_insert(order)
+    }
+}
+
+fn unindex<K: std::hash::Hash + Eq>(
+    index: &mut HashMap<K, Vec<UserRefNum>>,
+    key: K,
+    num: UserRefNum
+) {
+    if let Some(nums) = index.get_mut(&key) {
+        nums.retain(|n| *n != num);
+        if nums.is_empty() {
+            index.remove(&key);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+
+use crate::types::{
+    UserRefNum,
+    OrderToken,
+    StockSymbol,
+    Side,
+    Price,
+    TimeInForce,
+    Display,
+    Liquidity,
+};
+
+
+/// Lifecycle of an order, as far as the client knows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    /// Sent to the server, but not yet accepted or rejected.
+    Pending,
+    /// Accepted and open on the book, with no shares executed.
+    Live,
+    /// Open on the book, with some shares executed.
+    PartiallyFilled,
+    /// Every share of the order was executed.
+    Done,
+    /// Canceled, replaced, or accepted as dead before every share executed.
+    Dead,
+    /// Rejected by the server.
+    Rejected,
+}
+
+impl OrderStatus {
+
+    /// The order may still be executed (or is waiting to be accepted).
+    pub fn is_open(&self) -> bool {
+        use OrderStatus::*;
+        matches!(self, Pending | Live | PartiallyFilled)
+    }
+}
+
+
+/// An execution against an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fill {
+    match_number: u64,
+    quantity: u32,
+    price: Price,
+    liquidity: Liquidity,
+}
+
+impl Fill {
+
+    pub(crate) fn new(
+        match_number: u64,
+        quantity: u32,
+        price: Price,
+        liquidity: Liquidity,
+    ) -> Self {
+        Fill { match_number, quantity, price, liquidity }
+    }
+
+    /// Day-unique number assigned to the execution by the exchange.
+    pub fn match_number(&self) -> u64 { self.match_number }
+
+    /// Number of shares executed.
+    pub fn quantity(&self) -> u32 { self.quantity }
+
+    /// Price of the execution.
+    pub fn price(&self) -> Price { self.price }
+
+    /// Liquidity flag of the execution.
+    pub fn liquidity(&self) -> Liquidity { self.liquidity }
+}
+
+
+/// State of a single order (i.e., a single UserRefNum),
+/// built up from the requests sent and responses received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Order {
+    pub(crate) user_ref_num: UserRefNum,
+    pub(crate) order_token: OrderToken,
+    pub(crate) symbol: StockSymbol,
+    pub(crate) side: Side,
+    pub(crate) price: Price,
+    pub(crate) time_in_force: TimeInForce,
+    pub(crate) display: Display,
+    pub(crate) quantity: u32,
+    pub(crate) leaves_quantity: u32,
+    pub(crate) order_ref_num: Option<u64>,
+    pub(crate) status: OrderStatus,
+    pub(crate) fills: Vec<Fill>,
+}
+
+impl Order {
+
+    /// UserRefNum of the order.
+    pub fn user_ref_num(&self) -> UserRefNum { self.user_ref_num }
+
+    /// OrderToken (i.e., ClOrdId) of the order.
+    pub fn order_token(&self) -> OrderToken { self.order_token }
+
+    /// Symbol of the order.
+    pub fn symbol(&self) -> StockSymbol { self.symbol }
+
+    /// Side of the order, which may be changed by `ModifyOrder`.
+    pub fn side(&self) -> Side { self.side }
+
+    /// Limit price of the order.
+    pub fn price(&self) -> Price { self.price }
+
+    /// Time in force of the order.
+    pub fn time_in_force(&self) -> TimeInForce { self.time_in_force }
+
+    /// Display instructions of the order.
+    pub fn display(&self) -> Display { self.display }
+
+    /// Number of shares the order was entered (or accepted) with.
+    pub fn quantity(&self) -> u32 { self.quantity }
+
+    /// Number of shares that remain open on the book.
+    pub fn leaves_quantity(&self) -> u32 { self.leaves_quantity }
+
+    /// Number of shares executed, less any broken trades.
+    pub fn cumulative_quantity(&self) -> u32 {
+        self.fills.iter().map(|fill| fill.quantity).sum()
+    }
+
+    /// Volume-weighted average price of the executions (rounded down),
+    /// or `None` if nothing has been executed.
+    pub fn average_price(&self) -> Option<Price> {
+
+        let quantity = self.cumulative_quantity() as u128;
+        if quantity == 0 {
+            return None
+        }
+
+        let notional: u128 = self.fills.iter()
+            .map(|fill| fill.quantity as u128 * fill.price.val() as u128)
+            .sum();
+
+        Price::new((notional / quantity) as u64).ok()
+    }
+
+    /// Day-unique reference number assigned by the exchange on acceptance.
+    pub fn order_ref_num(&self) -> Option<u64> { self.order_ref_num }
+
+    /// Current status of the order.
+    pub fn status(&self) -> OrderStatus { self.status }
+
+    /// Executions against the order, in the order they were received.
+    pub fn fills(&self) -> &[Fill] { &self.fills }
+
+    // Shares were executed against the order.
+    pub(crate) fn execute(&mut self, fill: Fill) {
+
+        self.leaves_quantity =
+            self.leaves_quantity.saturating_sub(fill.quantity);
+        self.fills.push(fill);
+
+        if self.status.is_open() {
+            self.status = match self.leaves_quantity {
+                0 => OrderStatus::Done,
+                _ => OrderStatus::PartiallyFilled,
+            };
+        }
+    }
+
+    // Open shares were canceled, or the open quantity was modified.
+    pub(crate) fn set_leaves_quantity(&mut self, quantity: u32) {
+
+        self.leaves_quantity = quantity;
+
+        if self.status.is_open() {
+            self.status = match (quantity, self.fills.is_empty()) {
+                (0, _) => OrderStatus::Dead,
+                (_, true) => OrderStatus::Live,
+                (_, false) => OrderStatus::PartiallyFilled,
+            };
+        }
+    }
+
+    // The order is no longer on the book.
+    pub(crate) fn kill(&mut self) {
+        self.leaves_quantity = 0;
+        if self.status.is_open() {
+            self.status = OrderStatus::Dead;
+        }
+    }
+
+    // A broken trade no longer counts toward the executed shares,
+    // but the shares are not returned to the book.
+    pub(crate) fn bust(&mut self, match_number: u64) -> Option<Fill> {
+
+        let index = self.fills.iter()
+            .position(|fill| fill.match_number == match_number)?;
+
+        Some(self.fills.remove(index))
+    }
+}
@@ -0,0 +1,225 @@
+
+use std::collections::HashMap;
+
+use crate::{
+    msg::OuchResponse,
+    orders::OrderManager,
+    types::{ StockSymbol, Side, Price },
+};
+
+
+/// Keeps the net position, average cost and realized P&L of each symbol,
+/// built up from the executions received.
+///
+/// Executions take their symbol and side from the originating order,
+/// so pass each `OuchResponse` to `track_response` along with an
+/// `OrderManager` that is tracking the orders (or use `execute` directly).
+/// A `BrokenTrade` reverses the execution with the same match number.
+///
+/// P&L is given as a signed integer in units of $0.0001,
+/// the same units as `Price::val`.
+#[derive(Debug, Clone, Default)]
+pub struct PositionTracker {
+    positions: HashMap<[u8; 8], Position>,
+    // Symbol of each execution, so that broken trades can be found.
+    matches: HashMap<u64, [u8; 8]>,
+}
+
+impl PositionTracker {
+
+    /// Create a tracker with no positions.
+    pub fn new() -> Self {
+        PositionTracker::default()
+    }
+
+    /// Get the position in this symbol.
+    pub fn position(&self, symbol: &StockSymbol) -> Option<&Position> {
+        self.positions.get(&symbol.encode())
+    }
+
+    /// Every symbol with an execution (including flat positions).
+    pub fn positions(&self) -> impl Iterator<Item = &Position> {
+        self.positions.values()
+    }
+
+    /// Realized P&L across every symbol, in units of $0.0001.
+    pub fn realized_pnl(&self) -> i64 {
+        self.positions.values().map(|position| position.realized_pnl).sum()
+    }
+
+    /// Update the positions with a response received from the server.
+    /// `orders` provides the symbol and side of the originating order,
+    /// so it should already be tracking the order.
+    /// Returns the position affected by the response, if any.
+    pub fn track_response(
+        &mut self,
+        orders: &OrderManager,
+        response: &OuchResponse
+    ) -> Option<&Position> {
+
+        use OuchResponse::*;
+        match response {
+
+            OrderExecuted(executed) => {
+                let order = orders.get(executed.user_ref_num())?;
+                self.execute(
+                    order.symbol(),
+                    order.side(),
+                    executed.match_number(),
+                    executed.quantity(),
+                    executed.price(),
+                )
+            },
+
+            BrokenTrade(broken) => self.bust(broken.match_number()),
+
+            _ => None,
+        }
+    }
+
+    /// Apply an execution to the position in `symbol`.
+    /// Executions with a match number that was already applied are ignored.
+    pub fn execute(
+        &mut self,
+        symbol: StockSymbol,
+        side: Side,
+        match_number: u64,
+        quantity: u32,
+        price: Price,
+    ) -> Option<&Position> {
+
+        let key = symbol.encode();
+        if self.matches.contains_key(&match_number) {
+            return None
+        }
+        self.matches.insert(match_number, key);
+
+        let position = self.positions.entry(key)
+            .or_insert_with(|| Position::new(symbol));
+
+        position.executions.push(Execution {
+            match_number,
+            side,
+            quantity,
+            price,
+        });
+        position.apply(side, quantity, price);
+
+        Some(position)
+    }
+
+    /// Reverse the execution with this match number (e.g., a broken trade).
+    pub fn bust(&mut self, match_number: u64) -> Option<&Position> {
+
+        let key = self.matches.remove(&match_number)?;
+        let position = self.positions.get_mut(&key)?;
+
+        position.executions
+            .retain(|execution| execution.match_number != match_number);
+        position.replay();
+
+        Some(position)
+    }
+}
+
+
+// An execution is kept so that the position can be rebuilt without it.
+#[derive(Debug, Clone, Copy)]
+struct Execution {
+    match_number: u64,
+    side: Side,
+    quantity: u32,
+    price: Price,
+}
+
+
+/// Net position in a single symbol.
+#[derive(Debug, Clone)]
+pub struct Position {
+    symbol: StockSymbol,
+    executions: Vec<Execution>,
+    net_position: i64,
+    // Cost of the open shares, in units of $0.0001.
+    cost: i128,
+    realized_pnl: i64,
+}
+
+impl Position {
+
+    fn new(symbol: StockSymbol) -> Self {
+        Position {
+            symbol,
+            executions: Vec::new(),
+            net_position: 0,
+            cost: 0,
+            realized_pnl: 0,
+        }
+    }
+
+    /// Symbol of the position.
+    pub fn symbol(&self) -> StockSymbol { self.symbol }
+
+    /// Net shares held: positive if long, negative if short.
+    pub fn net_position(&self) -> i64 { self.net_position }
+
+    /// Average cost of the open shares (rounded down),
+    /// or `None` if the position is flat.
+    pub fn average_cost(&self) -> Option<Price> {
+
+        let shares = self.net_position.unsigned_abs() as i128;
+        if shares == 0 {
+            return None
+        }
+
+        Price::new((self.cost / shares) as u64).ok()
+    }
+
+    /// P&L realized by closing shares, in units of $0.0001.
+    pub fn realized_pnl(&self) -> i64 { self.realized_pnl }
+
+    // Short sales (exempt or not) reduce the position like any sale.
+    fn apply(&mut self, side: Side, quantity: u32, price: Price) {
+
+        let sign: i64 = match side {
+            Side::Buy => 1,
+            Side::Sell | Side::SellShort | Side::SellShortExempt => -1,
+        };
+
+        let quantity = quantity as i64;
+        let price = price.val() as i128;
+
+        // Shares that close out the open position realize P&L
+        // against the average cost; the rest open a new position.
+        let closing = match self.net_position.signum() == -sign {
+            true => quantity.min(self.net_position.abs()),
+            false => 0,
+        };
+
+        if closing > 0 {
+            let removed = self.cost * closing as i128
+                / self.net_position.abs() as i128;
+            let proceeds = closing as i128 * price;
+
+            self.realized_pnl += (-sign as i128 * (proceeds - removed)) as i64;
+            self.cost -= removed;
+            self.net_position += sign * closing;
+        }
+
+        let opening = quantity - closing;
+        self.cost += opening as i128 * price;
+        self.net_position += sign * opening;
+    }
+
+    fn replay(&mut self) {
+
+        self.net_position = 0;
+        self.cost = 0;
+        self.realized_pnl = 0;
+
+        let executions = std::mem::take(&mut self.executions);
+        for execution in &executions {
+            self.apply(execution.side, execution.quantity, execution.price);
+        }
+        self.executions = executions;
+    }
+}
@@ -0,0 +1,374 @@
+
+use std::collections::{ HashMap, HashSet };
+
+use crate::{
+    msg::{ OuchRequest, OuchResponse },
+    orders::OrderManager,
+    error::RiskViolation,
+    types::{ StockSymbol, Price },
+};
+
+
+/// Callback that is notified when a `RiskGate` refuses a request.
+pub type RejectionCallback =
+    Box<dyn FnMut(&OuchRequest, &RiskViolation) + Send>;
+
+
+/// Pre-trade risk checks for `EnterOrder`, `ReplaceOrder` and `ModifyOrder`
+/// requests, applied before they are sent to the server.
+/// Cancels (and other requests) are never refused.
+///
+/// Every limit is optional, and only checked once it is set.
+/// Notional values and exposures are in units of $0.0001
+/// (i.e., shares multiplied by `Price::val`).
+/// Open exposure counts the open shares of every order (long or short),
+/// including orders that have been sent but not yet accepted.
+///
+/// Use `OuchClient::set_risk_gate` (or the same method on any client or
+/// supervisor) to check every request sent through `send`.
+/// The gate keeps its own `OrderManager`, which is updated with every
+/// request the client writes to the server and every response it receives.
+/// The async clients track a request from when it is queued to be written;
+/// if its send fails or is cancelled before any of it is written,
+/// it is dropped and untracked.
+/// A request that fails to send is not tracked, though it is picked up
+/// from its `OrderAccepted` if it did reach the server.
+#[derive(Default)]
+pub struct RiskGate {
+    orders: OrderManager,
+    max_quantity: Option<u32>,
+    max_notional: Option<u64>,
+    price_collar: Option<u32>,
+    reference_prices: HashMap<[u8; 8], Price>,
+    max_symbol_exposure: Option<u64>,
+    max_exposure: Option<u64>,
+    restricted: HashSet<[u8; 8]>,
+    on_rejection: Option<RejectionCallback>,
+}
+
+impl RiskGate {
+
+    /// Create a risk gate with no limits.
+    pub fn new() -> Self {
+        RiskGate::default()
+    }
+
+    /// Refuse orders for more than this many shares.
+    pub fn set_max_quantity(&mut self, quantity: u32) {
+        self.max_quantity = Some(quantity)
+    }
+
+    /// Refuse orders with a notional value greater than this.
+    pub fn set_max_notional(&mut self, notional: u64) {
+        self.max_notional = Some(notional)
+    }
+
+    /// Refuse limit orders priced further than this many basis points
+    /// from the reference price of the symbol.
+    /// Symbols without a reference price are not checked.
+    pub fn set_price_collar(&mut self, basis_points: u32) {
+        self.price_collar = Some(basis_points)
+    }
+
+    /// Set the reference price of a symbol (e.g., the last trade),
+    /// which is used for price collars and to value market orders.
+    pub fn set_reference_price(&mut self, symbol: StockSymbol, price: Price) {
+        self.reference_prices.insert(symbol.encode(), price);
+    }
+
+    /// Refuse orders that would bring the open exposure in a symbol
+    /// above this value.
+    pub fn set_max_symbol_exposure(&mut self, exposure: u64) {
+        self.max_symbol_exposure = Some(exposure)
+    }
+
+    /// Refuse orders that would bring the open exposure across every
+    /// symbol above this value.
+    pub fn set_max_exposure(&mut self, exposure: u64) {
+        self.max_exposure = Some(exposure)
+    }
+
+    /// Refuse every order for this symbol.
+    pub fn restrict_symbol(&mut self, symbol: StockSymbol) {
+        self.restricted.insert(symbol.encode());
+    }
+
+    /// Allow orders for a symbol that was restricted.
+    pub fn unrestrict_symbol(&mut self, symbol: &StockSymbol) {
+        self.restricted.remove(&symbol.encode());
+    }
+
+    /// Register a callback that is notified of every refused request.
+    pub fn on_rejection(
+        &mut self,
+        callback: impl FnMut(&OuchRequest, &RiskViolation) + Send + 'static
+    ) {
+        self.on_rejection = Some(Box::new(callback))
+    }
+
+    /// The orders known to the gate.
+    pub fn orders(&self) -> &OrderManager {
+        &self.orders
+    }
+
+    /// Open exposure in a symbol.
+    pub fn symbol_exposure(&self, symbol: &StockSymbol) -> u64 {
+        self.orders.by_symbol(symbol)
+            .filter(|order| order.status().is_open())
+            .map(|order| self.value(
+                &order.symbol(),
+                order.leaves_quantity(),
+                order.price()
+            ))
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Open exposure across every symbol.
+    pub fn exposure(&self) -> u64 {
+        self.orders.open_orders()
+            .map(|order| self.value(
+                &order.symbol(),
+                order.leaves_quantity(),
+                order.price()
+            ))
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Check a request before it is sent.
+    /// Refused requests are reported to the rejection callback.
+    /// Allowed requests are not tracked until `track_request` is called
+    /// once they have been sent.
+    pub fn check(
+        &mut self,
+        request: &OuchRequest
+    ) -> Result<(), RiskViolation> {
+
+        if let Err(violation) = self.violation(request) {
+
+            #[cfg(feature = "logs")] {
+                log::warn!(
+                    "RiskGate refused {} request: {}",
+                    request,
+                    &violation
+                );
+            }
+
+            if let Some(callback) = &mut self.on_rejection {
+                callback(request, &violation)
+            }
+
+            return Err(violation)
+        }
+
+        Ok(())
+    }
+
+    /// Check a request and, if it is allowed, track it at once,
+    /// so that requests checked while it is being sent (e.g., from other
+    /// tasks) count it toward the limits.
+    /// Use `untrack_request` if it is not sent after all.
+    pub fn check_and_track(
+        &mut self,
+        request: &OuchRequest
+    ) -> Result<(), RiskViolation> {
+
+        self.check(request)?;
+        self.track_request(request);
+
+        Ok(())
+    }
+
+    /// Update the gate with a request that was sent to the server.
+    pub fn track_request(&mut self, request: &OuchRequest) {
+        self.orders.track_request(request);
+    }
+
+    /// Undo `track_request` for a request that was never sent
+    /// (e.g., because its send failed or was cancelled).
+    pub fn untrack_request(&mut self, request: &OuchRequest) {
+        self.orders.untrack_request(request);
+    }
+
+    /// Update the gate with a response received from the server.
+    pub fn track_response(&mut self, response: &OuchResponse) {
+        self.orders.track_response(response);
+    }
+
+    fn violation(&self, request: &OuchRequest) -> Result<(), RiskViolation> {
+
+        use OuchRequest::*;
+        match request {
+
+            EnterOrder(enter) => self.check_order(
+                enter.symbol(),
+                enter.quantity(),
+                enter.price(),
+                0,
+            ),
+
+            ReplaceOrder(replace) => {
+                let old = self.orders.get(replace.old_ref_num())
+                    .ok_or(RiskViolation::UnknownOrder(replace.old_ref_num()))?;
+
+                // The replaced order no longer counts once it is replaced.
+                let released = match old.status().is_open() {
+                    true => self.value(
+                        &old.symbol(),
+                        old.leaves_quantity(),
+                        old.price()
+                    ),
+                    false => 0,
+                };
+
+                self.check_order(
+                    old.symbol(),
+                    replace.quantity(),
+                    replace.price(),
+                    released,
+                )
+            },
+
+            ModifyOrder(modify) => {
+                let order = self.orders.get(modify.user_ref_num())
+                    .ok_or(RiskViolation::UnknownOrder(modify.user_ref_num()))?;
+
+                self.check_symbol(&order.symbol())?;
+                self.check_quantity(modify.quantity())?;
+
+                // Only the change in open shares affects the exposure.
+                let before = order.leaves_quantity();
+                let after = modify.quantity();
+                if after <= before {
+                    return Ok(())
+                }
+
+                let added = self.value(
+                    &order.symbol(),
+                    after - before,
+                    order.price()
+                );
+                self.check_exposure(&order.symbol(), added, 0)
+            },
+
+            _ => Ok(()),
+        }
+    }
+
+    // Check a new order, which may release the exposure of an order
+    // that it replaces.
+    fn check_order(
+        &self,
+        symbol: StockSymbol,
+        quantity: u32,
+        price: Price,
+        released: u64,
+    ) -> Result<(), RiskViolation> {
+
+        self.check_symbol(&symbol)?;
+        self.check_quantity(quantity)?;
+        self.check_collar(&symbol, price)?;
+
+        let notional = self.value(&symbol, quantity, price);
+        if let Some(limit) = self.max_notional
+        && notional > limit {
+            return Err(RiskViolation::MaxNotional { notional, limit })
+        }
+
+        self.check_exposure(&symbol, notional, released)
+    }
+
+    fn check_symbol(&self, symbol: &StockSymbol) -> Result<(), RiskViolation> {
+        match self.restricted.contains(&symbol.encode()) {
+            true => Err(RiskViolation::RestrictedSymbol(*symbol)),
+            false => Ok(()),
+        }
+    }
+
+    fn check_quantity(&self, quantity: u32) -> Result<(), RiskViolation> {
+        match self.max_quantity {
+            Some(limit) if quantity > limit => {
+                Err(RiskViolation::MaxQuantity { quantity, limit })
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn check_collar(
+        &self,
+        symbol: &StockSymbol,
+        price: Price,
+    ) -> Result<(), RiskViolation> {
+
+        let (Some(collar), Some(reference)) = (
+            self.price_collar,
+            self.reference_prices.get(&symbol.encode())
+        ) else {
+            return Ok(())
+        };
+
+        if is_market(price) {
+            return Ok(())
+        }
+
+        let price = price.val();
+        let reference = reference.val();
+        let band = reference as u128 * collar as u128 / 10_000;
+
+        if (price.abs_diff(reference) as u128) > band {
+            return Err(RiskViolation::PriceCollar { price, reference })
+        }
+
+        Ok(())
+    }
+
+    fn check_exposure(
+        &self,
+        symbol: &StockSymbol,
+        added: u64,
+        released: u64,
+    ) -> Result<(), RiskViolation> {
+
+        if let Some(limit) = self.max_symbol_exposure {
+            let exposure = self.symbol_exposure(symbol)
+                .saturating_add(added)
+                .saturating_sub(released);
+            if exposure > limit {
+                return Err(RiskViolation::SymbolExposure {
+                    symbol: *symbol,
+                    exposure,
+                    limit,
+                })
+            }
+        }
+
+        if let Some(limit) = self.max_exposure {
+            let exposure = self.exposure()
+                .saturating_add(added)
+                .saturating_sub(released);
+            if exposure > limit {
+                return Err(RiskViolation::TotalExposure { exposure, limit })
+            }
+        }
+
+        Ok(())
+    }
+
+    // Market orders are valued at the reference price, if there is one.
+    // Values too large to count saturate, so they are over any limit.
+    fn value(&self, symbol: &StockSymbol, quantity: u32, price: Price) -> u64 {
+
+        let price = match is_market(price) {
+            true => self.reference_prices.get(&symbol.encode())
+                .copied()
+                .unwrap_or(price),
+            false => price,
+        };
+
+        (quantity as u64).saturating_mul(price.val())
+    }
+}
+
+// Market orders are flagged by special price values.
+fn is_market(price: Price) -> bool {
+    price == Price::market() || price == Price::market_cross()
+}
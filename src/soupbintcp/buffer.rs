@@ -0,0 +1,58 @@
+
+use crate::error::OuchError;
+
+use super::{ ServerPacket, take_packet, is_sequenced };
+
+
+/// Accumulates bytes read from the server until they form whole packets.
+/// A single read may contain several packets, or only part of one,
+/// so any bytes left over after a packet are kept for the next.
+#[derive(Debug, Clone, Default)]
+pub struct PacketBuffer {
+    data: Vec<u8>,
+}
+
+impl PacketBuffer {
+
+    /// Create a new, empty buffer.
+    pub fn new() -> Self {
+        PacketBuffer { data: Vec::new() }
+    }
+
+    /// Add bytes received from the server.
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes)
+    }
+
+    /// Number of bytes received but not yet parsed.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Check if there are any bytes received but not yet parsed.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Discard all bytes received but not yet parsed.
+    pub fn clear(&mut self) {
+        self.data.clear()
+    }
+
+    /// Take the next whole packet from the buffer,
+    /// or `None` if more bytes are needed to complete it.
+    /// A packet that cannot be parsed is discarded, along with its length,
+    /// so that the following packet can still be read.
+    pub fn next_packet(&mut self) -> Result<Option<ServerPacket>, OuchError> {
+
+        let (consumed, result) = take_packet(&self.data, ServerPacket::parse);
+        self.data.drain(..consumed);
+        result
+    }
+
+    // Check if the next packet is sequenced data, even if it is malformed.
+    pub(crate) fn next_is_sequenced(&self) -> bool {
+        is_sequenced(&self.data)
+    }
+}
+
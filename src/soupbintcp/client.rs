@@ -0,0 +1,146 @@
+
+use crate::msg::OuchRequest;
+use crate::error::{ OuchError, ParseError };
+
+use super::{
+    frame,
+    unframe,
+    packet_type,
+    bad_packet_type,
+    parse_field,
+    parse_sequence_number,
+    encode_sequence_number,
+    Username,
+    Password,
+    SessionId,
+};
+
+
+/// Logical packets that the client sends to a SoupBinTCP server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientPacket {
+
+    /// Human-readable text, for debugging. Ignored by the server.
+    Debug(String),
+
+    /// Request to log in to a session.
+    /// A blank session logs in to the currently active session.
+    /// The sequence number is the next sequenced message to be received;
+    /// `0` requests that the server start from the most recent message.
+    LoginRequest {
+        username: Username,
+        password: Password,
+        session: SessionId,
+        sequence_number: u64,
+    },
+
+    /// Carries an OUCH request to the server.
+    UnsequencedData(OuchRequest),
+
+    /// Sent when the client has not sent anything for one second.
+    ClientHeartbeat,
+
+    /// Request to end the session.
+    /// The server responds with an End of Session packet.
+    LogoutRequest,
+}
+
+impl ClientPacket {
+
+    /// Get the packet type as a char.
+    pub fn packet_type(&self) -> char {
+
+        use ClientPacket::*;
+        match self {
+            Debug(..) => '+',
+            LoginRequest { .. } => 'L',
+            UnsequencedData(..) => 'U',
+            ClientHeartbeat => 'R',
+            LogoutRequest => 'O',
+        }
+    }
+
+    /// Encode packet to bytes, including the packet length.
+    /// Fails if the packet is too long for its 2-byte length
+    /// (e.g., a very long debug message).
+    pub fn encode(&self) -> Result<Vec<u8>, OuchError> {
+
+        use ClientPacket::*;
+        let payload: Vec<u8> = match self {
+
+            Debug(text) => text.as_bytes().to_vec(),
+
+            LoginRequest { username, password, session, sequence_number } => {
+                let mut bytes = Vec::with_capacity(46usize);
+                bytes.extend(username.encode());
+                bytes.extend(password.encode());
+                bytes.extend(session.encode());
+                bytes.extend(encode_sequence_number(*sequence_number));
+                bytes
+            },
+
+            UnsequencedData(request) => request.encode(),
+
+            ClientHeartbeat | LogoutRequest => vec![],
+        };
+
+        frame(self.packet_type() as u8, &payload)
+    }
+
+    /// Parse the next packet (including its length) from bytes,
+    /// as a server would.
+    /// Returns an incomplete `ParseError` if the packet has not been
+    /// fully read.
+    pub fn parse(input: &[u8]) -> Result<(&[u8], Self), ParseError> {
+
+        let (input, packet) = unframe(input)?;
+
+        // The packet is already complete, so a short packet
+        // is invalid data rather than a partial read.
+        let packet = Self::parse_packet(packet)
+            .map_err(ParseError::complete)?;
+
+        Ok((input, packet))
+    }
+
+    // Offsets are from the packet type.
+    fn parse_packet(packet: &[u8]) -> Result<Self, ParseError> {
+
+        let (packet_type, payload) = packet_type(packet)?;
+
+        use ClientPacket::*;
+        let packet = match packet_type {
+
+            b'+' => Debug(String::from_utf8_lossy(payload).into_owned()),
+
+            b'L' => {
+                let (payload, username) =
+                    parse_field(packet, payload, "username", Username::parse)?;
+                let (payload, password) =
+                    parse_field(packet, payload, "password", Password::parse)?;
+                let (payload, session) =
+                    parse_field(packet, payload, "session", SessionId::parse)?;
+                let (_, sequence_number) = parse_field(
+                    packet,
+                    payload,
+                    "sequence_number",
+                    parse_sequence_number
+                )?;
+                LoginRequest { username, password, session, sequence_number }
+            },
+
+            b'U' => {
+                let (_, request) = OuchRequest::parse(payload)?;
+                UnsequencedData(request)
+            },
+
+            b'R' => ClientHeartbeat,
+            b'O' => LogoutRequest,
+
+            _ => return Err(bad_packet_type(packet)),
+        };
+
+        Ok(packet)
+    }
+}
+
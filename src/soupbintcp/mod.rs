@@ -0,0 +1,156 @@
+
+/// Contains strong types used in SoupBinTCP session packets.
+mod session;
+
+/// Contains packets sent from the client to the server.
+mod client;
+
+/// Contains packets sent from the server to the client.
+mod server;
+
+/// Contains a buffer for reassembling packets from a byte stream.
+mod buffer;
+
+use crate::error::{ OuchError, ParseError, ParseErrorKind };
+
+pub use self::{
+    session::{
+        Username,
+        Password,
+        SessionId,
+        LoginRejectReason,
+    },
+    client::ClientPacket,
+    server::ServerPacket,
+    buffer::PacketBuffer,
+};
+
+
+// Each logical packet is prefixed with a 2-byte big-endian length,
+// which counts the packet type byte and the payload (but not itself).
+pub(crate) fn frame(
+    packet_type: u8,
+    payload: &[u8]
+) -> Result<Vec<u8>, OuchError> {
+
+    let length = u16::try_from(payload.len() + 1)
+        .map_err(|_| OuchError::PacketTooLong(payload.len() + 1))?;
+
+    let mut bytes: Vec<u8> = Vec::with_capacity(payload.len() + 3);
+    bytes.extend(length.to_be_bytes());
+    bytes.push(packet_type);
+    bytes.extend(payload);
+
+    Ok(bytes)
+}
+
+// Returns the next complete packet (its type and payload).
+// Streaming parsers are used so that an incomplete packet is reported
+// as incomplete, rather than as invalid data.
+pub(crate) fn unframe(input: &[u8]) -> Result<(&[u8], &[u8]), ParseError> {
+
+    let (rest, length) = nom::number::streaming::be_u16(input)
+        .map_err(|e| {
+            ParseError::from_nom(e, input, input).with_field("length")
+        })?;
+    let (rest, packet) = nom::bytes::streaming::take(length as usize)(rest)
+        .map_err(|e| {
+            ParseError::from_nom(e, input, rest).with_field("packet")
+        })?;
+
+    Ok((rest, packet))
+}
+
+// Split a packet into its type and payload.
+pub(crate) fn packet_type(packet: &[u8]) -> Result<(u8, &[u8]), ParseError> {
+    match packet.split_first() {
+        Some((packet_type, payload)) => Ok((*packet_type, payload)),
+        None => Err(ParseError::new(
+            ParseErrorKind::Invalid(nom::error::ErrorKind::Eof),
+            packet,
+            0
+        ).with_field("packet type")),
+    }
+}
+
+// An unrecognized packet type.
+pub(crate) fn bad_packet_type(packet: &[u8]) -> ParseError {
+    ParseError::new(
+        ParseErrorKind::Invalid(nom::error::ErrorKind::Tag),
+        packet,
+        0
+    ).with_field("packet type")
+}
+
+// Parse a field from the payload of a packet, which follows its type,
+// naming the field in any error.
+pub(crate) fn parse_field<'a, T>(
+    packet: &'a [u8],
+    at: &'a [u8],
+    field: &'static str,
+    parse: impl Fn(&'a [u8]) -> nom::IResult<&'a [u8], T>,
+) -> Result<(&'a [u8], T), ParseError> {
+    parse(at).map_err(|e| {
+        ParseError::from_nom(e, packet, at).with_field(field)
+    })
+}
+
+// Parse the next whole packet from the front of the input,
+// returning the number of bytes to consume along with the result.
+// `None` means that more bytes are needed to complete the packet.
+// A packet that cannot be parsed is skipped, along with its length,
+// so that the following packet can still be read.
+pub(crate) fn take_packet<'a, P>(
+    input: &'a [u8],
+    parse: impl Fn(&'a [u8]) -> Result<(&'a [u8], P), ParseError>,
+) -> (usize, Result<Option<P>, OuchError>) {
+
+    match parse(input) {
+
+        Ok((rest, packet)) => (input.len() - rest.len(), Ok(Some(packet))),
+
+        Err(e) if e.is_incomplete() => (0usize, Ok(None)),
+
+        // The length marker must have been read for the packet
+        // to be complete, so it can be used to skip the packet.
+        Err(e) => {
+            let length = u16::from_be_bytes([input[0], input[1]]);
+            (2 + length as usize, Err(OuchError::Parse(e)))
+        },
+    }
+}
+
+// Check if the next packet in the input carries sequenced data,
+// whether or not the rest of it can be parsed.
+pub(crate) fn is_sequenced(input: &[u8]) -> bool {
+    input.get(2) == Some(&b'S')
+}
+
+// Sequence numbers are sent as 20 bytes of ASCII digits,
+// padded on the left with spaces.
+pub(crate) fn parse_sequence_number(input: &[u8]) -> nom::IResult<&[u8], u64> {
+
+    let (input, digits) = nom::bytes::complete::take(20usize)(input)?;
+
+    let number = std::str::from_utf8(digits).ok()
+        .and_then(|s| s.trim_start().parse::<u64>().ok());
+
+    match number {
+        Some(number) => Ok((input, number)),
+        None => Err(nom::Err::Error(nom::error::Error::new(
+            digits,
+            nom::error::ErrorKind::Digit
+        ))),
+    }
+}
+
+pub(crate) fn encode_sequence_number(number: u64) -> [u8; 20] {
+
+    // u64::MAX has 20 digits, so this always fits.
+    let mut bytes = [b' '; 20];
+    let digits = number.to_string();
+    bytes[(20 - digits.len())..].copy_from_slice(digits.as_bytes());
+
+    bytes
+}
+
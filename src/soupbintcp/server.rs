@@ -0,0 +1,156 @@
+
+use crate::msg::OuchResponse;
+use crate::error::{ OuchError, ParseError };
+
+use super::{
+    frame,
+    unframe,
+    packet_type,
+    bad_packet_type,
+    parse_field,
+    parse_sequence_number,
+    encode_sequence_number,
+    SessionId,
+    LoginRejectReason,
+};
+
+
+/// Logical packets that a SoupBinTCP server sends to the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerPacket {
+
+    /// Human-readable text, for debugging. May be ignored.
+    Debug(String),
+
+    /// Login was successful.
+    /// The sequence number is that of the next sequenced message to be sent.
+    LoginAccepted {
+        session: SessionId,
+        sequence_number: u64,
+    },
+
+    /// Login failed. The server will close the connection.
+    LoginRejected(LoginRejectReason),
+
+    /// Carries an OUCH response from the server.
+    /// Each sequenced message implicitly increments the sequence number.
+    SequencedData(OuchResponse),
+
+    /// Messages that are not sequenced. Not used by OUCH 5.0.
+    UnsequencedData(Vec<u8>),
+
+    /// Sent when the server has not sent anything for one second.
+    ServerHeartbeat,
+
+    /// The session has ended. The server will close the connection.
+    EndOfSession,
+}
+
+impl ServerPacket {
+
+    /// Get the packet type as a char.
+    pub fn packet_type(&self) -> char {
+
+        use ServerPacket::*;
+        match self {
+            Debug(..) => '+',
+            LoginAccepted { .. } => 'A',
+            LoginRejected(..) => 'J',
+            SequencedData(..) => 'S',
+            UnsequencedData(..) => 'U',
+            ServerHeartbeat => 'H',
+            EndOfSession => 'Z',
+        }
+    }
+
+    /// Encode packet to bytes, including the packet length,
+    /// as a server would.
+    /// Fails if the packet is too long for its 2-byte length
+    /// (e.g., a very long debug message).
+    pub fn encode(&self) -> Result<Vec<u8>, OuchError> {
+
+        use ServerPacket::*;
+        let payload: Vec<u8> = match self {
+
+            Debug(text) => text.as_bytes().to_vec(),
+
+            LoginAccepted { session, sequence_number } => {
+                let mut bytes = Vec::with_capacity(30usize);
+                bytes.extend(session.encode());
+                bytes.extend(encode_sequence_number(*sequence_number));
+                bytes
+            },
+
+            LoginRejected(reason) => reason.encode().to_vec(),
+            SequencedData(response) => response.encode(),
+            UnsequencedData(data) => data.clone(),
+
+            ServerHeartbeat | EndOfSession => vec![],
+        };
+
+        frame(self.packet_type() as u8, &payload)
+    }
+
+    /// Parse the next packet (including its length) from bytes.
+    /// Returns an incomplete `ParseError` if the packet has not been
+    /// fully read.
+    pub fn parse(input: &[u8]) -> Result<(&[u8], Self), ParseError> {
+
+        let (input, packet) = unframe(input)?;
+
+        // The packet is already complete, so a short packet
+        // is invalid data rather than a partial read.
+        let packet = Self::parse_packet(packet)
+            .map_err(ParseError::complete)?;
+
+        Ok((input, packet))
+    }
+
+    // Offsets are from the packet type.
+    fn parse_packet(packet: &[u8]) -> Result<Self, ParseError> {
+
+        let (packet_type, payload) = packet_type(packet)?;
+
+        use ServerPacket::*;
+        let packet = match packet_type {
+
+            b'+' => Debug(String::from_utf8_lossy(payload).into_owned()),
+
+            b'A' => {
+                let (payload, session) =
+                    parse_field(packet, payload, "session", SessionId::parse)?;
+                let (_, sequence_number) = parse_field(
+                    packet,
+                    payload,
+                    "sequence_number",
+                    parse_sequence_number
+                )?;
+                LoginAccepted { session, sequence_number }
+            },
+
+            b'J' => {
+                let (_, reason) = parse_field(
+                    packet,
+                    payload,
+                    "reason",
+                    LoginRejectReason::parse
+                )?;
+                LoginRejected(reason)
+            },
+
+            b'S' => {
+                let (_, response) = OuchResponse::parse(payload)?;
+                SequencedData(response)
+            },
+
+            b'U' => UnsequencedData(payload.to_vec()),
+            b'H' => ServerHeartbeat,
+            b'Z' => EndOfSession,
+
+            _ => return Err(bad_packet_type(packet)),
+        };
+
+        Ok(packet)
+    }
+}
+
@@ -0,0 +1,151 @@
+
+use nsdq_util::{
+    define_str,
+    define_enum,
+    types::string::helper::*,
+};
+
+use nsdq_util::error::TypeError;
+
+define_str!{
+    Username [6usize]
+        "Username for logging in to a SoupBinTCP session.
+        Assigned during OUCH onboarding."
+}
+
+impl Username {
+
+    /// Generate a new Username from a protocol-compliant string.
+    /// May only contain ASCII alphanumeric characters.
+    /// Only up to the first 6 characters will be included, others ignored.
+    pub fn from(s: impl AsRef<str>) -> Result<Self, TypeError> {
+
+        let s = s.as_ref();
+
+        if !is_alphanumeric(s) {
+            return Err(
+                TypeError::InvalidString(
+                    "Username".to_string(),
+                    s.to_string()
+                )
+            );
+        }
+
+        Ok(Username(fixed_str::<6usize>(s)))
+    }
+}
+
+define_str!{
+    Password [10usize]
+        "Password for logging in to a SoupBinTCP session.
+        Assigned during OUCH onboarding."
+}
+
+impl Password {
+
+    /// Generate a new Password from a protocol-compliant string.
+    /// May only contain ASCII alphanumeric characters.
+    /// Only up to the first 10 characters will be included, others ignored.
+    pub fn from(s: impl AsRef<str>) -> Result<Self, TypeError> {
+
+        let s = s.as_ref();
+
+        if !is_alphanumeric(s) {
+            return Err(
+                TypeError::InvalidString(
+                    "Password".to_string(),
+                    s.to_string()
+                )
+            );
+        }
+
+        Ok(Password(fixed_str::<10usize>(s)))
+    }
+}
+
+
+/// Identifies a SoupBinTCP session.
+/// Unlike other strings in the protocol, this is padded on the LEFT.
+/// A blank session (the default) requests the currently active session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionId([u8; 10]);
+
+impl Default for SessionId {
+    fn default() -> Self { SessionId(*b"          ") }
+}
+
+impl SessionId {
+
+    /// Generate a new SessionId from a protocol-compliant string.
+    /// May only contain ASCII alphanumeric characters.
+    /// Only up to the first 10 characters will be included, others ignored.
+    pub fn from(s: impl AsRef<str>) -> Result<Self, TypeError> {
+
+        let s = s.as_ref();
+
+        if !is_alphanumeric(s) {
+            return Err(
+                TypeError::InvalidString(
+                    "SessionId".to_string(),
+                    s.to_string()
+                )
+            );
+        }
+
+        let chars = s.trim().as_bytes();
+        let copy_len = usize::min(10, chars.len());
+
+        let mut buf = [b' '; 10];
+        buf[(10 - copy_len)..].copy_from_slice(&chars[..copy_len]);
+
+        Ok(SessionId(buf))
+    }
+
+    /// Blank session, used to log in to the currently active session.
+    pub fn current() -> Self { Self::default() }
+
+    /// Check if the session is blank.
+    pub fn is_current(&self) -> bool { self.0.iter().all(|b| *b == b' ') }
+
+    /// NOTE: Assumes NASDAQ only sends valid characters,
+    /// and therefore does not check for compliance.
+    pub fn parse(input: &[u8]) -> nom::IResult<&[u8], Self> {
+
+        let (input, bytes) = nom::bytes::complete::take(10usize)(input)?;
+        let chars: [u8; 10] = bytes.try_into()
+            .expect("Take 10 bytes");
+
+        Ok((input, Self(chars)))
+    }
+
+    /// Extract the data for inclusion in a packet.
+    pub fn encode(&self) -> [u8; 10] { self.0 }
+
+    /// Character compliance should be checked when created.
+    ///
+    /// # Panics
+    /// Will panic if the string contains invalid UTF8.
+    pub fn to_str(&self) -> &str {
+        std::str::from_utf8(&self.0)
+            .expect("Character compliance should be checked by type")
+            .trim_start()
+    }
+}
+
+impl std::fmt::Display for SessionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_str().fmt(f)
+    }
+}
+
+
+define_enum!{
+
+    LoginRejectReason: "Reason a SoupBinTCP login request was rejected.";
+
+    ['A'] NotAuthorized
+        "There was an invalid username and password combination.",
+    ['S'] SessionNotAvailable
+        "The requested session does not exist or is not available.",
+}
+
@@ -0,0 +1,265 @@
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crate::{
+    AsyncOuchClient,
+    msg::{ OuchRequest, OuchResponse },
+    soupbintcp::{ Username, Password, SessionId },
+    error::OuchError,
+    types::UserRefNum,
+    risk::RiskGate,
+    throttle::Throttle,
+    kill::{ KillSwitch, KillReport },
+};
+
+use super::{
+    ConnectionState,
+    SupervisorState,
+    is_connection_error,
+};
+
+
+/// Keeps an `AsyncOuchClient` connected to one of a list of OUCH hosts.
+/// See `OuchSupervisor` for how the connection is recovered.
+pub struct AsyncOuchSupervisor {
+    state: SupervisorState,
+    timeout: Duration,
+    client: Option<AsyncOuchClient>,
+}
+
+impl AsyncOuchSupervisor {
+
+    /// Create a new supervisor for the hosts, which are tried in order.
+    /// `sequence_number` is the first sequenced message to request;
+    /// use `0` to start from the most recently generated message.
+    /// `timeout` is passed to each `AsyncOuchClient` that is logged in.
+    /// The supervisor does not connect until `connect`, `send` or `recv`
+    /// is called.
+    pub fn new(
+        hosts: Vec<SocketAddr>,
+        username: Username,
+        password: Password,
+        sequence_number: u64,
+        timeout: Duration,
+    ) -> Self {
+
+        AsyncOuchSupervisor {
+            state: SupervisorState::new(
+                hosts,
+                username,
+                password,
+                sequence_number
+            ),
+            timeout,
+            client: None,
+        }
+    }
+
+    /// Set a callback to be notified when the connection state changes.
+    pub fn on_state_change(
+        &mut self,
+        callback: impl FnMut(ConnectionState) + Send + 'static
+    ) {
+        self.state.on_state_change = Some(Box::new(callback))
+    }
+
+    /// Configure how long to wait after every host has failed to connect
+    /// before trying them again. Defaults to one second.
+    pub fn set_retry_delay(&mut self, duration: Duration) {
+        self.state.retry_delay = duration
+    }
+
+    /// Give up reconnecting after this many failed attempts.
+    /// By default, the supervisor will keep trying indefinitely.
+    pub fn set_max_attempts(&mut self, attempts: Option<usize>) {
+        self.state.max_attempts = attempts
+    }
+
+    /// The session is fixed by the first successful login,
+    /// so that later logins resume the same session.
+    pub fn session(&self) -> &SessionId {
+        &self.state.session
+    }
+
+    /// The sequence number that will be requested when logging back in.
+    pub fn sequence_number(&self) -> u64 {
+        match &self.client {
+            Some(client) => client.sequence_number(),
+            None => self.state.sequence_number,
+        }
+    }
+
+    /// Check if the supervisor currently has a connected client.
+    pub fn is_connected(&self) -> bool {
+        self.client.is_some()
+    }
+
+    /// Check every request sent through `send` with a `RiskGate`,
+    /// which is carried across reconnects.
+    /// See `OuchClient::set_risk_gate`.
+    pub fn set_risk_gate(&mut self, gate: RiskGate) {
+        match &mut self.client {
+            Some(client) => client.set_risk_gate(gate),
+            None => self.state.risk = Some(gate),
+        }
+    }
+
+    /// Get mutable access to the risk gate
+    /// (e.g., to update its reference prices).
+    pub fn risk_gate(&mut self) -> Option<&mut RiskGate> {
+        match &mut self.client {
+            Some(client) => client.risk_gate(),
+            None => self.state.risk.as_mut(),
+        }
+    }
+
+    /// Limit the rate of requests sent through `send` with a `Throttle`,
+    /// which is carried across reconnects.
+    /// See `OuchClient::set_throttle`.
+    pub fn set_throttle(&mut self, throttle: Throttle) {
+        match &mut self.client {
+            Some(client) => client.set_throttle(throttle),
+            None => self.state.throttle = Some(throttle),
+        }
+    }
+
+    /// Get mutable access to the throttle (e.g., to change its limits).
+    pub fn throttle(&mut self) -> Option<&mut Throttle> {
+        match &mut self.client {
+            Some(client) => client.throttle(),
+            None => self.state.throttle.as_mut(),
+        }
+    }
+
+    /// Get access to the connected client, if there is one.
+    pub fn client(&mut self) -> Option<&mut AsyncOuchClient> {
+        self.client.as_mut()
+    }
+
+    /// Get the next valid UserRefNum, which is carried across reconnects.
+    /// See `OuchClient::new_user_ref_num`.
+    pub fn new_user_ref_num(&mut self) -> UserRefNum {
+        match &mut self.client {
+            Some(client) => client.new_user_ref_num(),
+            None => self.state.new_user_ref_num(),
+        }
+    }
+
+    // Record where to resume before dropping the lost client.
+    fn disconnect(&mut self) {
+
+        if let Some(mut client) = self.client.take() {
+            self.state.risk = client.take_risk_gate();
+            self.state.throttle = client.take_throttle();
+            self.state.disconnected(
+                client.sequence_number(),
+                *client.next_user_ref_num()
+            );
+        }
+    }
+
+    /// Connect to the first available host, retrying until connected
+    /// (or until the maximum number of attempts is reached).
+    pub async fn connect(&mut self) -> Result<(), OuchError> {
+
+        if self.client.is_some() { return Ok(()) }
+
+        loop {
+            let addr = self.state.next_addr()?;
+
+            let error = match AsyncOuchClient::login(
+                addr,
+                self.state.username,
+                self.state.password,
+                self.state.session,
+                self.state.sequence_number,
+                self.timeout,
+            ).await {
+                Ok(mut client) => {
+                    client.sync_user_ref_num(self.state.next_user_ref_num);
+                    if let Some(gate) = self.state.risk.take() {
+                        client.set_risk_gate(gate);
+                    }
+                    if let Some(throttle) = self.state.throttle.take() {
+                        client.set_throttle(throttle);
+                    }
+                    self.state.connected(*client.session(), addr);
+                    self.client = Some(client);
+                    return Ok(())
+                },
+                Err(error) => error,
+            };
+
+            // Every host has been tried, so wait before trying again.
+            let delay = self.state.failed(error)?;
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+
+    /// Send an OUCH request, connecting first if necessary.
+    /// If the connection is lost while sending, the error is returned,
+    /// and the supervisor will reconnect on the next call.
+    /// The request is not re-sent after reconnecting; check the replayed
+    /// responses before repeating it (with the same UserRefNum).
+    pub async fn send(&mut self, msg: OuchRequest) -> Result<(), OuchError> {
+
+        self.connect().await?;
+        let client = self.client.as_mut().expect("Client is connected");
+
+        let result = client.send(msg).await;
+        if let Err(error) = &result
+        && is_connection_error(error) {
+            self.disconnect()
+        }
+
+        result
+    }
+
+    /// Cancel everything and optionally disable order entry,
+    /// connecting first if needed. See `OuchClient::kill_switch`.
+    pub async fn kill_switch(
+        &mut self,
+        kill: &KillSwitch
+    ) -> Result<KillReport, OuchError> {
+
+        self.connect().await?;
+        let client = self.client.as_mut().expect("Client is connected");
+
+        let result = client.kill_switch(kill).await;
+        if let Err(error) = &result
+        && is_connection_error(error) {
+            self.disconnect()
+        }
+
+        result
+    }
+
+    /// Receive the next OUCH response, reconnecting if the connection is lost.
+    /// Responses missed during the outage are replayed after reconnecting.
+    pub async fn recv(&mut self) -> Result<OuchResponse, OuchError> {
+
+        loop {
+            self.connect().await?;
+            let client = self.client.as_mut().expect("Client is connected");
+
+            match client.recv().await {
+                Err(error) if is_connection_error(&error) => {
+
+                    #[cfg(feature = "logs")] {
+                        log::warn!(
+                            "OuchSupervisor lost connection: {}",
+                            &error
+                        );
+                    }
+
+                    self.disconnect()
+                },
+                result => return result,
+            }
+        }
+    }
+}
+
@@ -0,0 +1,182 @@
+
+/// Contains the supervisor for the blocking client.
+mod blocking;
+
+/// Contains the supervisor for the asynchronous client.
+#[cfg(feature = "async")]
+mod asynchronous;
+
+pub use self::blocking::OuchSupervisor;
+
+#[cfg(feature = "async")]
+pub use self::asynchronous::AsyncOuchSupervisor;
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crate::{
+    soupbintcp::{
+        Username,
+        Password,
+        SessionId,
+        LoginRejectReason,
+    },
+    error::OuchError,
+    types::UserRefNum,
+    risk::RiskGate,
+    throttle::Throttle,
+};
+
+
+/// Changes in the connection to the OUCH server.
+/// Order generation should be paused while not `Connected`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+
+    /// Attempting to connect and log in to the host at this address.
+    Connecting(SocketAddr),
+
+    /// Logged in to the host at this address and synced with the account.
+    Connected(SocketAddr),
+
+    /// The connection was lost and will be re-established on the next
+    /// call to `send` or `recv`.
+    Disconnected,
+}
+
+/// Callback that is notified when the connection state changes.
+pub type StateCallback = Box<dyn FnMut(ConnectionState) + Send>;
+
+
+// Everything needed to log back in, shared by both supervisors.
+struct SupervisorState {
+    hosts: Vec<SocketAddr>,
+    next_host: usize,
+    username: Username,
+    password: Password,
+    session: SessionId,
+    sequence_number: u64,
+    next_user_ref_num: UserRefNum,
+    retry_delay: Duration,
+    max_attempts: Option<usize>,
+    attempts: usize,
+    on_state_change: Option<StateCallback>,
+    // Held by the client while connected.
+    risk: Option<RiskGate>,
+    throttle: Option<Throttle>,
+}
+
+impl SupervisorState {
+
+    fn new(
+        hosts: Vec<SocketAddr>,
+        username: Username,
+        password: Password,
+        sequence_number: u64,
+    ) -> Self {
+
+        SupervisorState {
+            hosts,
+            next_host: 0usize,
+            username,
+            password,
+            session: SessionId::current(),
+            sequence_number,
+            next_user_ref_num: UserRefNum::new(),
+            retry_delay: Duration::from_secs(1),
+            max_attempts: None,
+            attempts: 0usize,
+            on_state_change: None,
+            risk: None,
+            throttle: None,
+        }
+    }
+
+    fn notify(&mut self, state: ConnectionState) {
+
+        #[cfg(feature = "logs")] {
+            log::info!("OuchSupervisor connection state: {:?}", &state);
+        }
+
+        if let Some(callback) = &mut self.on_state_change {
+            callback(state)
+        }
+    }
+
+    fn new_user_ref_num(&mut self) -> UserRefNum {
+        let new_num = self.next_user_ref_num;
+        self.next_user_ref_num.increment();
+        new_num
+    }
+
+    // Pick the next host to try, cycling through the list,
+    // and announce the attempt.
+    fn next_addr(&mut self) -> Result<SocketAddr, OuchError> {
+
+        if self.hosts.is_empty() {
+            return Err(std::io::Error::from(
+                std::io::ErrorKind::AddrNotAvailable
+            ).into())
+        }
+
+        let addr = self.hosts[self.next_host];
+        self.next_host = (self.next_host + 1) % self.hosts.len();
+        self.notify(ConnectionState::Connecting(addr));
+
+        Ok(addr)
+    }
+
+    // Record where to resume, taken from the lost client.
+    fn disconnected(&mut self, sequence_number: u64, next: UserRefNum) {
+        self.sequence_number = sequence_number;
+        self.next_user_ref_num = next;
+        self.notify(ConnectionState::Disconnected);
+    }
+
+    // Later logins resume the session fixed by the first.
+    fn connected(&mut self, session: SessionId, addr: SocketAddr) {
+        self.session = session;
+        self.attempts = 0usize;
+        self.notify(ConnectionState::Connected(addr));
+    }
+
+    // Decide whether to keep trying after a failed login.
+    // Returns the delay before the next attempt,
+    // which is only needed once every host has been tried.
+    fn failed(&mut self, error: OuchError) -> Result<Duration, OuchError> {
+
+        #[cfg(feature = "logs")] {
+            log::warn!("OuchSupervisor failed to connect: {}", &error);
+        }
+
+        self.attempts += 1;
+        if is_fatal_error(&error)
+        || self.max_attempts.is_some_and(|max| self.attempts >= max) {
+            self.attempts = 0usize;
+            self.notify(ConnectionState::Disconnected);
+            return Err(error)
+        }
+
+        if self.next_host == 0 {
+            Ok(self.retry_delay)
+        } else {
+            Ok(Duration::ZERO)
+        }
+    }
+}
+
+// Only these should prompt a reconnect; other errors are returned.
+fn is_connection_error(error: &OuchError) -> bool {
+    matches!(error,
+        OuchError::ClientIo(..) |
+        OuchError::HeartbeatTimeout(..)
+    )
+}
+
+// Bad credentials will not succeed on any host.
+fn is_fatal_error(error: &OuchError) -> bool {
+    matches!(error,
+        OuchError::LoginRejected(LoginRejectReason::NotAuthorized)
+    )
+}
+
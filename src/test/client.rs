@@ -0,0 +1,916 @@
+
+use std::io::{ self, Cursor, Read, Write };
+
+use crate::{
+    OuchClient,
+    msg::OuchResponse,
+    soupbintcp::*,
+    error::OuchError,
+    types::EventCode,
+};
+
+
+// Packets from the server, shared with the supervisor tests.
+
+pub(super) fn login_accepted(sequence: u64) -> Vec<u8> {
+
+    let mut packet: Vec<u8> = 31u16.to_be_bytes().to_vec();
+    packet.push(b'A');
+    packet.extend(b"     SESS1");
+    packet.extend(format!("{:>20}", sequence).as_bytes());
+    packet
+}
+
+pub(super) fn account_query_response(next_user_ref_num: u32) -> Vec<u8> {
+
+    let mut packet: Vec<u8> = 16u16.to_be_bytes().to_vec();
+    packet.push(b'S');
+    packet.push(b'Q');
+    packet.extend(1u64.to_be_bytes());
+    packet.extend(next_user_ref_num.to_be_bytes());
+    packet.extend(0u16.to_be_bytes());
+    packet
+}
+
+pub(super) fn system_event(code: u8) -> Vec<u8> {
+
+    let mut packet: Vec<u8> = 11u16.to_be_bytes().to_vec();
+    packet.push(b'S');
+    packet.push(b'S');
+    packet.extend(1u64.to_be_bytes());
+    packet.push(code);
+    packet
+}
+
+// AccountQuery request, as sent by the client.
+const ACCOUNT_QUERY: [u8; 6] = [0, 4, b'U', b'Q', 0, 0];
+
+// Client heartbeat packet.
+const HEARTBEAT: [u8; 3] = [0, 1, b'R'];
+
+// In-memory transport that replays the server's bytes
+// and records the client's.
+struct MockStream {
+    input: Cursor<Vec<u8>>,
+    output: Vec<u8>,
+    silent: bool,
+    broken: bool,
+}
+
+impl MockStream {
+    fn new(input: Vec<u8>) -> Self {
+        MockStream {
+            input: Cursor::new(input),
+            output: Vec::new(),
+            silent: false,
+            broken: false,
+        }
+    }
+
+    // Once the input is used up, reads time out (as a socket would
+    // with a read timeout) rather than the server hanging up.
+    fn silent(input: Vec<u8>) -> Self {
+        MockStream { silent: true, ..MockStream::new(input) }
+    }
+}
+
+impl Read for MockStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.input.read(buf)?;
+        if n == 0 && self.silent {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            return Err(io::ErrorKind::WouldBlock.into())
+        }
+        Ok(n)
+    }
+}
+
+impl Write for MockStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.broken {
+            return Err(io::ErrorKind::BrokenPipe.into())
+        }
+        self.output.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+fn start_of_day(response: OuchResponse) {
+    match response {
+        OuchResponse::SystemEvent(event) => {
+            assert_eq!(event.event_code(), EventCode::StartOfDay)
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+}
+
+#[test] fn wrap_stream() {
+
+    let mut input = account_query_response(7);
+    input.extend(system_event(b'S'));
+
+    let mut client = OuchClient::wrap_stream(MockStream::new(input)).unwrap();
+    assert_eq!(client.next_user_ref_num().val(), 7u32);
+    assert_eq!(client.stream().output, ACCOUNT_QUERY.to_vec());
+
+    start_of_day(client.recv().unwrap());
+
+    // The stream is exhausted, as if the server hung up.
+    match client.recv() {
+        Err(OuchError::ClientIo(e)) => {
+            assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof)
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+}
+
+#[test] fn heartbeat() {
+
+    use std::time::Duration;
+
+    let input = account_query_response(7);
+    let mut client = OuchClient::wrap_stream(MockStream::new(input)).unwrap();
+    client.set_heartbeat_interval(Duration::from_millis(100));
+
+    // Nothing is sent until the interval has passed.
+    client.keep_alive().unwrap();
+    assert_eq!(client.stream().output, ACCOUNT_QUERY.to_vec());
+
+    std::thread::sleep(Duration::from_millis(100));
+    client.keep_alive().unwrap();
+    let mut should_be = ACCOUNT_QUERY.to_vec();
+    should_be.extend(HEARTBEAT);
+    assert_eq!(client.stream().output, should_be);
+
+    // Sending a heartbeat restarts the interval.
+    client.keep_alive().unwrap();
+    assert_eq!(client.stream().output, should_be);
+}
+
+#[test] fn heartbeat_timeout() {
+
+    use std::time::Duration;
+
+    let input = account_query_response(7);
+    let stream = MockStream::silent(input);
+    let mut client = OuchClient::wrap_stream(stream).unwrap();
+    client.set_heartbeat_interval(Duration::from_millis(5));
+    client.set_heartbeat_timeout(Duration::from_millis(50));
+
+    // The server goes silent, so `recv` gives up on it.
+    match client.recv() {
+        Err(OuchError::HeartbeatTimeout(silence)) => {
+            assert!(silence >= Duration::from_millis(50))
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+
+    // Heartbeats were sent while waiting.
+    let output = &client.stream().output;
+    let heartbeats = &output[ACCOUNT_QUERY.len()..];
+    assert!(!heartbeats.is_empty());
+    for packet in heartbeats.chunks(3) {
+        assert_eq!(packet, HEARTBEAT);
+    }
+}
+
+#[cfg(feature = "async")]
+#[tokio::test] async fn async_heartbeat_timeout() {
+
+    use std::time::Duration;
+    use tokio::io::{ AsyncReadExt, AsyncWriteExt };
+    use crate::AsyncOuchClient;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    server.write_all(&account_query_response(7)).await.unwrap();
+
+    let mut client = AsyncOuchClient::wrap_stream(
+        stream,
+        Duration::from_secs(1)
+    ).await.unwrap();
+    client.set_heartbeat_interval(Duration::from_millis(5));
+    client.set_heartbeat_timeout(Duration::from_millis(50));
+
+    match client.recv().await {
+        Err(OuchError::HeartbeatTimeout(silence)) => {
+            assert!(silence >= Duration::from_millis(50))
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+
+    let mut request = [0u8; 9];
+    server.read_exact(&mut request).await.unwrap();
+    assert_eq!(request[..6], ACCOUNT_QUERY);
+    assert_eq!(request[6..], HEARTBEAT);
+}
+
+#[test] fn risk_gate() {
+
+    use crate::{ risk::RiskGate, orders::OrderStatus };
+    use super::orders::{ user_ref_num, enter_order, accepted };
+
+    let mut input = account_query_response(7);
+    input.extend(ServerPacket::SequencedData(
+        accepted(7, b'B', 100, b'L')
+    ).encode().unwrap());
+
+    let mut client = OuchClient::wrap_stream(MockStream::new(input)).unwrap();
+
+    let mut gate = RiskGate::new();
+    gate.set_max_quantity(500);
+    client.set_risk_gate(gate);
+
+    // Refused requests are never sent.
+    match client.send(enter_order(7, crate::types::Side::Buy, 600)) {
+        Err(OuchError::RiskRejected(..)) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    assert_eq!(client.stream().output, ACCOUNT_QUERY.to_vec());
+
+    client.send(enter_order(7, crate::types::Side::Buy, 100)).unwrap();
+    assert!(client.stream().output.len() > ACCOUNT_QUERY.len());
+
+    // The gate is kept up-to-date with the responses received.
+    client.recv().unwrap();
+    let gate = client.risk_gate().unwrap();
+    let order = gate.orders().get(user_ref_num(7)).unwrap();
+    assert_eq!(order.status(), OrderStatus::Live);
+}
+
+#[test] fn risk_gate_send_failed() {
+
+    use crate::{ risk::RiskGate, orders::OrderStatus, types::* };
+    use super::orders::{ user_ref_num, enter_order };
+
+    let input = account_query_response(7);
+    let mut client = OuchClient::wrap_stream(MockStream::new(input)).unwrap();
+    client.set_risk_gate(RiskGate::new());
+
+    // Requests that were never written are not tracked.
+    client.stream().broken = true;
+    match client.send(enter_order(7, Side::Buy, 100)) {
+        Err(OuchError::ClientIo(..)) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    let gate = client.risk_gate().unwrap();
+    assert!(gate.orders().get(user_ref_num(7)).is_none());
+    assert_eq!(gate.exposure(), 0);
+
+    client.stream().broken = false;
+    client.send(enter_order(8, Side::Buy, 100)).unwrap();
+    let gate = client.risk_gate().unwrap();
+    let order = gate.orders().get(user_ref_num(8)).unwrap();
+    assert_eq!(order.status(), OrderStatus::Pending);
+    assert_eq!(gate.exposure(), 100 * 3_5000);
+}
+
+#[test] fn throttle() {
+
+    use std::time::{ Duration, Instant };
+    use crate::{ cancel, throttle::{ Throttle, ThrottleMode } };
+    use super::orders::{ user_ref_num, enter_order };
+
+    let input = account_query_response(7);
+    let mut client = OuchClient::wrap_stream(MockStream::new(input)).unwrap();
+
+    let mut throttle = Throttle::new();
+    throttle.set_port_rate(50, 1);
+    throttle.set_mode(ThrottleMode::Refuse);
+    client.set_throttle(throttle);
+
+    // Requests over the limit are refused without being sent.
+    client.send(enter_order(7, crate::types::Side::Buy, 100)).unwrap();
+    let sent = client.stream().output.len();
+    match client.send(enter_order(8, crate::types::Side::Buy, 100)) {
+        Err(OuchError::Throttled(..)) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    assert_eq!(client.stream().output.len(), sent);
+
+    // Cancels go through regardless.
+    client.send(cancel!{
+        user_ref_num: user_ref_num(7),
+        quantity: 0u32,
+    }).unwrap();
+    assert!(client.stream().output.len() > sent);
+
+    // Queued requests wait in `send` until they can be sent.
+    client.throttle().unwrap().set_mode(ThrottleMode::Queue);
+    let start = Instant::now();
+    client.send(enter_order(8, crate::types::Side::Buy, 100)).unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(20));
+}
+
+#[test] fn risk_gate_before_throttle() {
+
+    use crate::{ risk::RiskGate, throttle::{ Throttle, ThrottleMode } };
+    use super::orders::enter_order;
+
+    let input = account_query_response(7);
+    let mut client = OuchClient::wrap_stream(MockStream::new(input)).unwrap();
+
+    let mut gate = RiskGate::new();
+    gate.set_max_quantity(500);
+    client.set_risk_gate(gate);
+
+    let mut throttle = Throttle::new();
+    throttle.set_port_rate(1, 1);
+    throttle.set_mode(ThrottleMode::Refuse);
+    client.set_throttle(throttle);
+
+    // A refused request does not use up the throttle.
+    match client.send(enter_order(7, crate::types::Side::Buy, 600)) {
+        Err(OuchError::RiskRejected(..)) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    client.send(enter_order(8, crate::types::Side::Buy, 100)).unwrap();
+}
+
+#[test] fn kill_switch() {
+
+    use crate::{ kill::KillSwitch, risk::RiskGate, types::* };
+    use super::orders::{ enter_order, accepted };
+    use super::kill::{ mass_canceled, entry_disabled };
+
+    let mut input = account_query_response(7);
+    input.extend(ServerPacket::SequencedData(
+        accepted(7, b'B', 100, b'L')
+    ).encode().unwrap());
+    input.extend(ServerPacket::SequencedData(
+        mass_canceled(8, "STONKS")
+    ).encode().unwrap());
+    input.extend(
+        ServerPacket::SequencedData(entry_disabled(9)).encode().unwrap()
+    );
+
+    let mut client = OuchClient::wrap_stream(MockStream::new(input)).unwrap();
+    client.set_risk_gate(RiskGate::new());
+    let num = client.new_user_ref_num();
+    client.send(enter_order(num.val(), Side::Buy, 100)).unwrap();
+
+    // Live orders are found through the risk gate.
+    let mut kill = KillSwitch::new(Mpid::from("FIRM").unwrap());
+    kill.set_disable_entry(true);
+    let report = client.kill_switch(&kill).unwrap();
+
+    assert!(report.is_complete());
+    assert!(report.entry_disabled());
+    assert_eq!(
+        report.acknowledged(),
+        &[StockSymbol::from("STONKS").unwrap()]
+    );
+
+    // Responses received while waiting are still returned by `recv`.
+    for _ in 0..3 {
+        client.recv().unwrap();
+    }
+}
+
+#[test] fn kill_switch_failures() {
+
+    use crate::{ kill::KillSwitch, types::* };
+    use super::kill::mass_canceled;
+
+    // A malformed packet arrives ahead of the acknowledgement.
+    let mut input = account_query_response(7);
+    input.extend([0, 3, b'S', b'S', 0]);
+    input.extend(ServerPacket::SequencedData(
+        mass_canceled(7, "STONKS")
+    ).encode().unwrap());
+
+    let mut client = OuchClient::wrap_stream(MockStream::new(input)).unwrap();
+
+    let mut kill = KillSwitch::new(Mpid::from("FIRM").unwrap());
+    kill.add_symbol(StockSymbol::from("STONKS").unwrap());
+    let report = client.kill_switch(&kill).unwrap();
+
+    assert!(report.is_complete());
+    assert_eq!(report.errors().len(), 1);
+
+    // Every request is still tried when sending fails.
+    client.stream().broken = true;
+    kill.add_symbol(StockSymbol::from("MEME").unwrap());
+    kill.set_disable_entry(true);
+    let report = client.kill_switch(&kill).unwrap();
+
+    assert!(!report.is_complete());
+    assert!(!report.entry_disabled());
+    assert_eq!(report.unsent(), kill.symbols());
+    assert_eq!(report.unacknowledged(), kill.symbols());
+
+    // Nothing is awaited, since nothing was sent.
+    assert_eq!(report.errors().len(), 3);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test] async fn async_kill_switch_malformed_packet() {
+
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+    use crate::{ AsyncOuchClient, kill::KillSwitch, types::* };
+    use super::kill::mass_canceled;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    server.write_all(&account_query_response(7)).await.unwrap();
+
+    let mut client = AsyncOuchClient::wrap_stream(
+        stream,
+        Duration::from_secs(1)
+    ).await.unwrap();
+
+    server.write_all(&[0, 3, b'S', b'S', 0]).await.unwrap();
+    server.write_all(&ServerPacket::SequencedData(
+        mass_canceled(7, "STONKS")
+    ).encode().unwrap()).await.unwrap();
+
+    let mut kill = KillSwitch::new(Mpid::from("FIRM").unwrap());
+    kill.add_symbol(StockSymbol::from("STONKS").unwrap());
+
+    let report = client.kill_switch(&kill).await.unwrap();
+    assert!(report.is_complete());
+    assert_eq!(report.errors().len(), 1);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test] async fn async_kill_switch() {
+
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+    use crate::{ AsyncOuchClient, kill::KillSwitch, types::* };
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    server.write_all(&account_query_response(7)).await.unwrap();
+
+    let mut client = AsyncOuchClient::wrap_stream(
+        stream,
+        Duration::from_secs(1)
+    ).await.unwrap();
+
+    // The server never acknowledges, so the kill switch times out.
+    let mut kill = KillSwitch::new(Mpid::from("FIRM").unwrap());
+    kill.add_symbol(StockSymbol::from("STONKS").unwrap());
+    kill.set_timeout(Duration::from_millis(20));
+
+    let report = client.kill_switch(&kill).await.unwrap();
+    assert!(!report.is_complete());
+    assert_eq!(
+        report.unacknowledged(),
+        vec![StockSymbol::from("STONKS").unwrap()]
+    );
+}
+
+#[test] fn login_stream() {
+
+    // The replayed message arrives ahead of the AccountQueryResponse.
+    let mut input = login_accepted(4);
+    input.extend(system_event(b'S'));
+    input.extend(account_query_response(7));
+
+    let mut client = OuchClient::login_stream(
+        MockStream::new(input),
+        Username::from("USER").unwrap(),
+        Password::from("PASSWORD").unwrap(),
+        SessionId::current(),
+        4u64,
+    ).unwrap();
+
+    assert_eq!(client.session().to_str(), "SESS1");
+    assert_eq!(client.sequence_number(), 5u64);
+
+    let output = &client.stream().output;
+    assert_eq!(output[2], b'L');
+    assert_eq!(&output[49..], &ACCOUNT_QUERY);
+
+    start_of_day(client.recv().unwrap());
+    assert_eq!(client.sequence_number(), 6u64);
+}
+
+#[test] fn sequence_malformed_packet() {
+
+    // A System Event that is too short to parse.
+    let mut input = login_accepted(4);
+    input.extend(account_query_response(7));
+    input.extend([0, 3, b'S', b'S', 0]);
+    input.extend(system_event(b'S'));
+
+    let mut client = OuchClient::login_stream(
+        MockStream::new(input),
+        Username::from("USER").unwrap(),
+        Password::from("PASSWORD").unwrap(),
+        SessionId::current(),
+        4u64,
+    ).unwrap();
+    assert_eq!(client.sequence_number(), 5u64);
+
+    match client.recv() {
+        Err(OuchError::Parse(..)) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    assert_eq!(client.sequence_number(), 6u64);
+
+    // The malformed packet still counts toward the sequence.
+    start_of_day(client.recv().unwrap());
+    assert_eq!(client.sequence_number(), 7u64);
+}
+
+#[test] fn login_rejected() {
+
+    let input = vec![0, 2, b'J', b'A'];
+
+    let result = OuchClient::login_stream(
+        MockStream::new(input),
+        Username::from("USER").unwrap(),
+        Password::from("PASSWORD").unwrap(),
+        SessionId::current(),
+        0u64,
+    );
+
+    match result {
+        Err(OuchError::LoginRejected(reason)) => {
+            assert_eq!(reason, LoginRejectReason::NotAuthorized)
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+}
+
+#[test] fn logout() {
+
+    let mut input = account_query_response(7);
+    input.extend(system_event(b'S'));
+    input.extend([0, 1, b'Z']);
+
+    let client = OuchClient::wrap_stream(MockStream::new(input)).unwrap();
+    let responses = client.logout().unwrap();
+
+    assert_eq!(responses.len(), 1);
+    start_of_day(responses[0].clone());
+}
+
+#[cfg(feature = "async")]
+#[tokio::test] async fn async_wrap_stream() {
+
+    use tokio::io::{ AsyncReadExt, AsyncWriteExt };
+    use crate::AsyncOuchClient;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+
+    let mut input = account_query_response(7);
+    input.extend(system_event(b'S'));
+    server.write_all(&input).await.unwrap();
+
+    let mut client = AsyncOuchClient::wrap_stream(
+        stream,
+        std::time::Duration::from_secs(1)
+    ).await.unwrap();
+    assert_eq!(client.next_user_ref_num().val(), 7u32);
+
+    let mut request = [0u8; 6];
+    server.read_exact(&mut request).await.unwrap();
+    assert_eq!(request, ACCOUNT_QUERY);
+
+    start_of_day(client.recv().await.unwrap());
+
+    // The server hung up.
+    drop(server);
+    match client.recv().await {
+        Err(OuchError::ClientIo(e)) => {
+            assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof)
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+}
+
+#[cfg(feature = "async")]
+#[tokio::test] async fn async_split() {
+
+    use tokio::io::{ AsyncReadExt, AsyncWriteExt };
+    use crate::{ AsyncOuchClient, account_query };
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    server.write_all(&account_query_response(7)).await.unwrap();
+
+    let client = AsyncOuchClient::wrap_stream(
+        stream,
+        std::time::Duration::from_secs(1)
+    ).await.unwrap();
+
+    let (mut reader, writer) = client.into_split();
+    let handle = writer.user_ref_nums();
+
+    // The reader waits while the writer sends from another task.
+    let waiting = tokio::spawn(async move { reader.recv().await });
+
+    let sending = writer.clone();
+    tokio::spawn(async move {
+        sending.send(account_query!()).await.unwrap()
+    }).await.unwrap();
+
+    let mut request = [0u8; 12];
+    server.read_exact(&mut request).await.unwrap();
+    assert_eq!(request[..6], ACCOUNT_QUERY);
+    assert_eq!(request[6..], ACCOUNT_QUERY);
+
+    server.write_all(&account_query_response(9)).await.unwrap();
+    match waiting.await.unwrap().unwrap() {
+        OuchResponse::AccountQueryResponse(..) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+
+    // Every handle draws from the same sequence.
+    assert_eq!(handle.new_user_ref_num().val(), 9u32);
+    assert_eq!(writer.new_user_ref_num().val(), 10u32);
+    assert_eq!(handle.next_user_ref_num().val(), 11u32);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test] async fn async_split_risk_gate() {
+
+    use std::{ future::Future, pin::pin, task::{ Context, Waker } };
+    use tokio::io::{ AsyncReadExt, AsyncWriteExt };
+    use crate::{
+        AsyncOuchClient,
+        account_query,
+        risk::RiskGate,
+        throttle::Throttle,
+        error::RiskViolation,
+        types::Side,
+    };
+    use super::orders::enter_order;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    server.write_all(&account_query_response(7)).await.unwrap();
+
+    let mut client = AsyncOuchClient::wrap_stream(
+        stream,
+        std::time::Duration::from_secs(1)
+    ).await.unwrap();
+
+    let mut gate = RiskGate::new();
+    gate.set_max_exposure(150 * 3_5000);
+    client.set_risk_gate(gate);
+
+    let mut throttle = Throttle::new();
+    throttle.set_port_rate(20, 1);
+    client.set_throttle(throttle);
+
+    let (_reader, writer) = client.into_split();
+    let racing = writer.clone();
+    writer.send(account_query!()).await.unwrap();
+
+    let mut cx = Context::from_waker(Waker::noop());
+
+    // The first order waits on the throttle, but already counts.
+    let mut first = pin!(writer.send(enter_order(7, Side::Buy, 100)));
+    assert!(first.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(writer.risk_gate().unwrap().exposure(), 100 * 3_5000);
+
+    match racing.send(enter_order(8, Side::Buy, 100)).await {
+        Err(OuchError::RiskRejected(RiskViolation::TotalExposure {
+            exposure,
+            ..
+        })) => assert_eq!(exposure, 200 * 3_5000),
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+
+    // A send cancelled while it waits no longer counts.
+    {
+        let mut cancelled = pin!(racing.send(enter_order(9, Side::Buy, 10)));
+        assert!(cancelled.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(writer.risk_gate().unwrap().exposure(), 110 * 3_5000);
+    }
+    assert_eq!(writer.risk_gate().unwrap().exposure(), 100 * 3_5000);
+
+    first.await.unwrap();
+
+    // Requests to sync the client, then to use up the throttle.
+    let order = enter_order(7, Side::Buy, 100);
+    let mut sent = [ACCOUNT_QUERY, ACCOUNT_QUERY].concat();
+    sent.extend(ClientPacket::UnsequencedData(order).encode().unwrap());
+
+    let mut requests = vec![0u8; sent.len()];
+    server.read_exact(&mut requests).await.unwrap();
+    assert_eq!(requests, sent);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test] async fn async_recv_cancelled() {
+
+    use std::time::Duration;
+    use tokio::{ io::AsyncWriteExt, time::timeout };
+    use crate::AsyncOuchClient;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    server.write_all(&account_query_response(7)).await.unwrap();
+
+    let mut client = AsyncOuchClient::wrap_stream(
+        stream,
+        Duration::from_secs(1)
+    ).await.unwrap();
+
+    // Cancel the receive after only part of the packet has arrived.
+    let event = system_event(b'S');
+    server.write_all(&event[..4]).await.unwrap();
+    let cancelled = timeout(Duration::from_millis(10), client.recv()).await;
+    assert!(cancelled.is_err());
+
+    server.write_all(&event[4..]).await.unwrap();
+    start_of_day(client.recv().await.unwrap());
+}
+
+// Async transport over an in-memory pipe that stops taking writes
+// (returning `Pending`) once its write budget is used up.
+#[cfg(feature = "async")]
+struct StallStream {
+    inner: tokio::io::DuplexStream,
+    budget: Option<usize>,
+}
+
+#[cfg(feature = "async")]
+impl tokio::io::AsyncRead for StallStream {
+    fn poll_read(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        std::pin::Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+#[cfg(feature = "async")]
+impl tokio::io::AsyncWrite for StallStream {
+    fn poll_write(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<io::Result<usize>> {
+
+        use std::task::Poll;
+        let len = match self.budget {
+            Some(0) => return Poll::Pending,
+            Some(budget) => budget.min(buf.len()),
+            None => buf.len(),
+        };
+
+        let poll = std::pin::Pin::new(&mut self.inner)
+            .poll_write(cx, &buf[..len]);
+        if let (Poll::Ready(Ok(n)), Some(budget)) = (&poll, self.budget) {
+            self.budget = Some(budget - n);
+        }
+        poll
+    }
+    fn poll_flush(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        std::pin::Pin::new(&mut self.inner).poll_flush(cx)
+    }
+    fn poll_shutdown(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        std::pin::Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(feature = "async")]
+#[tokio::test] async fn async_send_cancelled() {
+
+    use std::{ future::Future, pin::pin, task::{ Context, Waker } };
+    use tokio::io::{ AsyncReadExt, AsyncWriteExt };
+    use crate::{ AsyncOuchClient, account_query };
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    server.write_all(&account_query_response(7)).await.unwrap();
+
+    let stream = StallStream { inner: stream, budget: None };
+    let mut client = AsyncOuchClient::wrap_stream(
+        stream,
+        std::time::Duration::from_secs(1)
+    ).await.unwrap();
+
+    let mut request = [0u8; 6];
+    server.read_exact(&mut request).await.unwrap();
+    assert_eq!(request, ACCOUNT_QUERY);
+
+    // Room for one and a half requests.
+    client.stream().budget = Some(9);
+    client.send(account_query!()).await.unwrap();
+
+    // Cancel the second request after it has been partly written.
+    {
+        let mut sending = pin!(client.send(account_query!()));
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(sending.as_mut().poll(&mut cx).is_pending());
+    }
+    assert_eq!(client.stream().budget, Some(0));
+
+    // The rest of the interrupted request is written before the next.
+    client.stream().budget = None;
+    client.send(account_query!()).await.unwrap();
+
+    let mut requests = [0u8; 18];
+    server.read_exact(&mut requests).await.unwrap();
+    for request in requests.chunks(6) {
+        assert_eq!(request, ACCOUNT_QUERY);
+    }
+}
+
+#[cfg(feature = "async")]
+#[tokio::test] async fn async_send_cancelled_unwritten() {
+
+    use std::{ future::Future, pin::pin, task::{ Context, Waker } };
+    use tokio::io::{ AsyncReadExt, AsyncWriteExt };
+    use crate::{ AsyncOuchClient, account_query, types::Side };
+    use super::orders::enter_order;
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    server.write_all(&account_query_response(7)).await.unwrap();
+
+    let stream = StallStream { inner: stream, budget: None };
+    let mut client = AsyncOuchClient::wrap_stream(
+        stream,
+        std::time::Duration::from_secs(1)
+    ).await.unwrap();
+
+    let mut request = [0u8; 6];
+    server.read_exact(&mut request).await.unwrap();
+
+    // Cancel the order before any of it has been written.
+    client.stream().budget = Some(0);
+    {
+        let mut sending = pin!(client.send(enter_order(7, Side::Buy, 100)));
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(sending.as_mut().poll(&mut cx).is_pending());
+    }
+
+    // The cancelled order is never sent.
+    client.stream().budget = None;
+    client.send(account_query!()).await.unwrap();
+    client.stream().shutdown().await.unwrap();
+
+    let mut requests = Vec::new();
+    server.read_to_end(&mut requests).await.unwrap();
+    assert_eq!(requests, ACCOUNT_QUERY);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test] async fn async_risk_gate_send_cancelled() {
+
+    use std::{ future::Future, pin::pin, task::{ Context, Waker } };
+    use tokio::io::{ AsyncReadExt, AsyncWriteExt };
+    use crate::{
+        AsyncOuchClient,
+        account_query,
+        risk::RiskGate,
+        types::Side,
+    };
+    use super::orders::{ user_ref_num, enter_order };
+
+    let (stream, mut server) = tokio::io::duplex(1024);
+    server.write_all(&account_query_response(7)).await.unwrap();
+
+    let stream = StallStream { inner: stream, budget: None };
+    let mut client = AsyncOuchClient::wrap_stream(
+        stream,
+        std::time::Duration::from_secs(1)
+    ).await.unwrap();
+    client.set_risk_gate(RiskGate::new());
+
+    let mut request = [0u8; 6];
+    server.read_exact(&mut request).await.unwrap();
+
+    let mut cx = Context::from_waker(Waker::noop());
+
+    // The first order is cancelled before any of it is written.
+    client.stream().budget = Some(0);
+    {
+        let mut sending = pin!(client.send(enter_order(7, Side::Buy, 100)));
+        assert!(sending.as_mut().poll(&mut cx).is_pending());
+    }
+
+    // The second is cancelled after it has been partly written.
+    client.stream().budget = Some(5);
+    {
+        let mut sending = pin!(client.send(enter_order(8, Side::Buy, 200)));
+        assert!(sending.as_mut().poll(&mut cx).is_pending());
+    }
+
+    client.stream().budget = None;
+    client.send(account_query!()).await.unwrap();
+    client.stream().shutdown().await.unwrap();
+
+    // Only the second order reached the server, and only it is tracked.
+    let order = enter_order(8, Side::Buy, 200);
+    let mut sent = ClientPacket::UnsequencedData(order).encode().unwrap();
+    sent.extend(ACCOUNT_QUERY);
+
+    let mut requests = Vec::new();
+    server.read_to_end(&mut requests).await.unwrap();
+    assert_eq!(requests, sent);
+
+    let gate = client.risk_gate().unwrap();
+    assert!(gate.orders().get(user_ref_num(7)).is_none());
+    assert!(gate.orders().get(user_ref_num(8)).is_some());
+    assert_eq!(gate.exposure(), 200 * 3_5000);
+}
@@ -0,0 +1,128 @@
+
+use bytes::BytesMut;
+use tokio_util::codec::{ Decoder, Encoder };
+
+use crate::{
+    account_query,
+    codec::*,
+    msg::{ OuchRequest, OuchResponse },
+    soupbintcp::ClientPacket,
+    error::OuchError,
+};
+
+use super::client::{
+    login_accepted,
+    account_query_response,
+    system_event,
+};
+
+
+#[test] fn decode_responses() {
+
+    let mut codec = OuchCodec::new();
+
+    let mut src = BytesMut::new();
+    src.extend(login_accepted(5));
+    src.extend([0, 1, b'H']);
+    src.extend(account_query_response(7));
+
+    // Only part of the next packet has arrived.
+    let event = system_event(b'S');
+    src.extend(&event[..4]);
+
+    match codec.decode(&mut src).unwrap() {
+        Some(OuchResponse::AccountQueryResponse(aqr)) => {
+            assert_eq!(aqr.next_user_ref_num().val(), 7u32)
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    assert_eq!(codec.session().to_str(), "SESS1");
+    assert_eq!(codec.sequence_number(), 6u64);
+
+    assert!(codec.decode(&mut src).unwrap().is_none());
+
+    src.extend(&event[4..]);
+    match codec.decode(&mut src).unwrap() {
+        Some(OuchResponse::SystemEvent(..)) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    assert_eq!(codec.sequence_number(), 7u64);
+    assert!(src.is_empty());
+}
+
+#[test] fn decode_malformed_packet() {
+
+    let mut codec = OuchCodec::new();
+
+    // A System Event that is too short to parse.
+    let mut src = BytesMut::new();
+    src.extend(login_accepted(5));
+    src.extend([0, 3, b'S', b'S', 0]);
+    src.extend(system_event(b'S'));
+
+    match codec.decode(&mut src) {
+        Err(OuchError::Parse(..)) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    assert_eq!(codec.sequence_number(), 6u64);
+
+    // The malformed packet still counts toward the sequence.
+    match codec.decode(&mut src).unwrap() {
+        Some(OuchResponse::SystemEvent(..)) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    assert_eq!(codec.sequence_number(), 7u64);
+}
+
+#[test] fn decode_end_of_session() {
+
+    let mut codec = OuchCodec::new();
+    let mut src = BytesMut::from(&[0, 1, b'Z'][..]);
+
+    match codec.decode(&mut src) {
+        Err(OuchError::EndOfSession) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+}
+
+#[test] fn encode_requests() {
+
+    let mut codec = OuchCodec::new();
+    let mut dst = BytesMut::new();
+
+    codec.encode(account_query!(), &mut dst).unwrap();
+    codec.encode(ClientPacket::ClientHeartbeat, &mut dst).unwrap();
+
+    let mut should_be = ClientPacket::UnsequencedData(account_query!())
+        .encode().unwrap();
+    should_be.extend(ClientPacket::ClientHeartbeat.encode().unwrap());
+    assert_eq!(dst.to_vec(), should_be);
+}
+
+#[test] fn client_to_server() {
+
+    let mut client = OuchCodec::new();
+    let mut server = OuchServerCodec::new(1u64);
+    let mut wire = BytesMut::new();
+
+    client.encode(ClientPacket::ClientHeartbeat, &mut wire).unwrap();
+    client.encode(account_query!(), &mut wire).unwrap();
+
+    let request: Option<OuchRequest> = server.decode(&mut wire).unwrap();
+    assert_eq!(request, Some(account_query!()));
+
+    // Strip the packet framing to get the OUCH message.
+    let data = account_query_response(7);
+    let (_, response) = OuchResponse::parse(&data[3..]).unwrap();
+
+    server.encode(response.clone(), &mut wire).unwrap();
+    assert_eq!(server.sequence_number(), 2u64);
+
+    assert_eq!(client.decode(&mut wire).unwrap(), Some(response));
+
+    client.encode(ClientPacket::LogoutRequest, &mut wire).unwrap();
+    match server.decode(&mut wire) {
+        Err(OuchError::EndOfSession) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+}
@@ -0,0 +1,107 @@
+
+use crate::{
+    kill::KillSwitch,
+    orders::OrderManager,
+    msg::{ OuchRequest, OuchResponse },
+    types::*,
+};
+
+use super::orders::{
+    response,
+    user_ref_num,
+    enter_order,
+    accepted,
+    canceled,
+    rejected,
+};
+
+
+pub(super) fn mass_canceled(num: u32, symbol: &str) -> OuchResponse {
+
+    let mut data: Vec<u8> = vec![b'X'];
+    data.extend(1u64.to_be_bytes());
+    data.extend(num.to_be_bytes());
+    data.extend(b"FIRM");
+    data.extend(format!("{:<8}", symbol).as_bytes());
+    data.extend(0u16.to_be_bytes());
+    response(data)
+}
+
+pub(super) fn entry_disabled(num: u32) -> OuchResponse {
+
+    let mut data: Vec<u8> = vec![b'G'];
+    data.extend(1u64.to_be_bytes());
+    data.extend(num.to_be_bytes());
+    data.extend(b"FIRM");
+    data.extend(0u16.to_be_bytes());
+    response(data)
+}
+
+fn symbol(symbol: &str) -> StockSymbol {
+    StockSymbol::from(symbol).unwrap()
+}
+
+
+#[test] fn kill_requests() {
+
+    let mut orders = OrderManager::new();
+    orders.track_request(&enter_order(1, Side::Buy, 100));
+    orders.track_response(&accepted(1, b'B', 100, b'L'));
+    orders.track_request(&enter_order(2, Side::Sell, 100));
+    orders.track_response(&accepted(2, b'S', 100, b'L'));
+
+    let mut kill = KillSwitch::new(Mpid::from("FIRM").unwrap());
+    kill.add_symbol(symbol("MEME"));
+    kill.add_orders(&orders);
+    kill.set_disable_entry(true);
+    assert_eq!(kill.symbols(), &[symbol("MEME"), symbol("STONKS")]);
+
+    let mut next = user_ref_num(10);
+    let (requests, mut report) = kill.requests(None, &mut next);
+    assert_eq!(next.val(), 13u32);
+    assert_eq!(requests.len(), 3);
+
+    match &requests[1] {
+        OuchRequest::MassCancel(cancel) => {
+            assert_eq!(cancel.user_ref_num().val(), 11u32);
+            assert_eq!(cancel.symbol(), symbol("STONKS"));
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    match &requests[2] {
+        OuchRequest::DisableOrderEntry(disable) => {
+            assert_eq!(disable.user_ref_num().val(), 12u32);
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+
+    // Unrelated responses are ignored.
+    report.acknowledge(&canceled(1, 100));
+    report.acknowledge(&mass_canceled(11, "STONKS"));
+    assert!(!report.is_settled());
+
+    report.acknowledge(&rejected(10));
+    report.acknowledge(&entry_disabled(12));
+    assert!(report.is_settled());
+    assert!(!report.is_complete());
+
+    assert_eq!(report.acknowledged(), &[symbol("STONKS")]);
+    assert_eq!(report.unacknowledged(), vec![symbol("MEME")]);
+    assert!(report.entry_disabled());
+}
+
+#[test] fn kill_nothing_open() {
+
+    let mut orders = OrderManager::new();
+    orders.track_request(&enter_order(1, Side::Buy, 100));
+    orders.track_response(&accepted(1, b'B', 100, b'L'));
+    orders.track_response(&canceled(1, 100));
+
+    let kill = KillSwitch::new(Mpid::from("FIRM").unwrap());
+    let mut next = user_ref_num(10);
+    let (requests, report) = kill.requests(Some(&orders), &mut next);
+
+    assert!(requests.is_empty());
+    assert!(report.is_settled());
+    assert!(report.is_complete());
+}
@@ -1,5 +1,16 @@
+// Byte arrays are built up field-by-field for readability.
+#![allow(clippy::vec_init_then_push)]
+
 
 #[cfg(test)] mod request;
 #[cfg(test)] mod response;
 #[cfg(test)] mod options;
-
+#[cfg(test)] mod soupbintcp;
+#[cfg(test)] mod client;
+#[cfg(test)] mod supervisor;
+#[cfg(test)] mod orders;
+#[cfg(test)] mod positions;
+#[cfg(test)] mod risk;
+#[cfg(test)] mod throttle;
+#[cfg(test)] mod kill;
+#[cfg(all(test, feature = "codec"))] mod codec;
@@ -165,7 +165,7 @@ use crate::{
 
     let (_bytes, tv) = TagValue::parse(&bytes).unwrap();
     match tv {
-        TagValue::PostOnly(val) => assert_eq!(val, false),
+        TagValue::PostOnly(val) => assert!(!val),
         _ => panic!("Where'd you find this?"),
     }
 
@@ -279,7 +279,7 @@ use crate::{
 
     let (_bytes, tv) = TagValue::parse(&bytes).unwrap();
     match tv {
-        TagValue::SharesLocated(val) => assert_eq!(val, true),
+        TagValue::SharesLocated(val) => assert!(val),
         _ => panic!("Where'd you find this?"),
     }
 
@@ -351,3 +351,16 @@ use crate::{
 
 }
 
+
+#[test] fn unknown_tag() {
+
+    // Include an option tag that is not in the spec, ignore length marker
+    let bytes = vec![99u8, 1, 2, 3];
+
+    let (bytes, tv) = TagValue::parse(&bytes).unwrap();
+    assert!(bytes.is_empty());
+    assert_eq!(tv, TagValue::Unknown { tag: 99u8, bytes: vec![1, 2, 3] });
+
+    // Re-encode with its length marker
+    assert_eq!(tv.encode(), vec![4u8, 99, 1, 2, 3]);
+}
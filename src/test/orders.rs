@@ -0,0 +1,416 @@
+
+use crate::{
+    enter,
+    replace,
+    orders::{ OrderManager, OrderStatus },
+    msg::{ OuchRequest, OuchResponse },
+    types::*,
+};
+
+
+// Parse a response message built for a test.
+pub(super) fn response(data: Vec<u8>) -> OuchResponse {
+    let (_, response) = OuchResponse::parse(&data)
+        .expect("Should be valid data");
+    response
+}
+
+pub(super) fn user_ref_num(num: u32) -> UserRefNum {
+    let (_, num) = UserRefNum::parse(&num.to_be_bytes())
+        .expect("Should be valid data");
+    num
+}
+
+pub(super) fn enter_order(
+    num: u32,
+    side: Side,
+    quantity: u32
+) -> OuchRequest {
+    enter!{
+        user_ref_num: user_ref_num(num),
+        side: side,
+        quantity: quantity,
+        symbol: StockSymbol::from("STONKS").unwrap(),
+        price: Price::new(35000).unwrap(),
+        time_in_force: TimeInForce::Day,
+        display: Display::Visible,
+        capacity: Capacity::Agency,
+        intermarket_sweep: false,
+        cross_type: CrossType::ContinuousMarket,
+        order_token: OrderToken::from("To The Moon").unwrap()
+    }
+}
+
+pub(super) fn accepted(
+    num: u32,
+    side: u8,
+    quantity: u32,
+    state: u8
+) -> OuchResponse {
+
+    let mut data: Vec<u8> = vec![b'A'];
+    data.extend(1u64.to_be_bytes());
+    data.extend(num.to_be_bytes());
+    data.push(side);
+    data.extend(quantity.to_be_bytes());
+    data.extend(b"STONKS  ");
+    data.extend(35000u64.to_be_bytes());
+    data.push(b'0');
+    data.push(b'Y');
+    data.extend((num as u64 + 100).to_be_bytes());
+    data.push(b'A');
+    data.push(b'N');
+    data.push(b'N');
+    data.push(state);
+    data.extend(b"To The Moon   ");
+    data.extend(0u16.to_be_bytes());
+    response(data)
+}
+
+pub(super) fn replaced(
+    old: u32,
+    new: u32,
+    quantity: u32,
+    price: u64,
+    state: u8
+) -> OuchResponse {
+
+    let mut data: Vec<u8> = vec![b'U'];
+    data.extend(1u64.to_be_bytes());
+    data.extend(old.to_be_bytes());
+    data.extend(new.to_be_bytes());
+    data.push(b'B');
+    data.extend(quantity.to_be_bytes());
+    data.extend(b"STONKS  ");
+    data.extend(price.to_be_bytes());
+    data.push(b'0');
+    data.push(b'Y');
+    data.extend((new as u64 + 100).to_be_bytes());
+    data.push(b'A');
+    data.push(b'N');
+    data.push(b'N');
+    data.push(state);
+    data.extend(b"Replaced      ");
+    data.extend(0u16.to_be_bytes());
+    response(data)
+}
+
+pub(super) fn executed(
+    num: u32,
+    quantity: u32,
+    price: u64,
+    match_number: u64
+) -> OuchResponse {
+
+    let mut data: Vec<u8> = vec![b'E'];
+    data.extend(1u64.to_be_bytes());
+    data.extend(num.to_be_bytes());
+    data.extend(quantity.to_be_bytes());
+    data.extend(price.to_be_bytes());
+    data.push(b'A');
+    data.extend(match_number.to_be_bytes());
+    data.extend(0u16.to_be_bytes());
+    response(data)
+}
+
+pub(super) fn canceled(num: u32, quantity: u32) -> OuchResponse {
+
+    let mut data: Vec<u8> = vec![b'C'];
+    data.extend(1u64.to_be_bytes());
+    data.extend(num.to_be_bytes());
+    data.extend(quantity.to_be_bytes());
+    data.push(b'U');
+    data.extend(0u16.to_be_bytes());
+    response(data)
+}
+
+pub(super) fn rejected(num: u32) -> OuchResponse {
+
+    let mut data: Vec<u8> = vec![b'J'];
+    data.extend(1u64.to_be_bytes());
+    data.extend(num.to_be_bytes());
+    data.extend(0x0006_u16.to_be_bytes());
+    data.extend(b"To The Moon   ");
+    data.extend(0u16.to_be_bytes());
+    response(data)
+}
+
+pub(super) fn broken(num: u32, match_number: u64) -> OuchResponse {
+
+    let mut data: Vec<u8> = vec![b'B'];
+    data.extend(1u64.to_be_bytes());
+    data.extend(num.to_be_bytes());
+    data.extend(match_number.to_be_bytes());
+    data.push(b'E');
+    data.extend(b"To The Moon   ");
+    data.extend(0u16.to_be_bytes());
+    response(data)
+}
+
+
+#[test] fn order_lifecycle() {
+
+    let mut orders = OrderManager::new();
+    let num = user_ref_num(1);
+
+    orders.track_request(&enter_order(1, Side::Buy, 300));
+    assert_eq!(orders.get(num).unwrap().status(), OrderStatus::Pending);
+
+    orders.track_response(&accepted(1, b'B', 300, b'L'));
+    let order = orders.get(num).unwrap();
+    assert_eq!(order.status(), OrderStatus::Live);
+    assert_eq!(order.order_ref_num(), Some(101));
+    assert_eq!(order.leaves_quantity(), 300);
+
+    orders.track_response(&executed(1, 100, 35000, 1));
+    orders.track_response(&executed(1, 100, 34000, 2));
+    let order = orders.get(num).unwrap();
+    assert_eq!(order.status(), OrderStatus::PartiallyFilled);
+    assert_eq!(order.leaves_quantity(), 100);
+    assert_eq!(order.cumulative_quantity(), 200);
+    assert_eq!(order.average_price(), Some(Price::new(34500).unwrap()));
+
+    // Repeated executions are only counted once.
+    orders.track_response(&executed(1, 100, 34000, 2));
+    assert_eq!(orders.get(num).unwrap().cumulative_quantity(), 200);
+
+    orders.track_response(&executed(1, 100, 35000, 3));
+    let order = orders.get(num).unwrap();
+    assert_eq!(order.status(), OrderStatus::Done);
+    assert_eq!(order.leaves_quantity(), 0);
+
+    // Broken trades no longer count toward the executed shares.
+    orders.track_response(&broken(1, 2));
+    let order = orders.get(num).unwrap();
+    assert_eq!(order.cumulative_quantity(), 200);
+    assert_eq!(order.average_price(), Some(Price::new(35000).unwrap()));
+}
+
+#[test] fn order_canceled() {
+
+    let mut orders = OrderManager::new();
+    let num = user_ref_num(1);
+
+    orders.track_request(&enter_order(1, Side::Sell, 300));
+    orders.track_response(&accepted(1, b'S', 300, b'L'));
+    orders.track_response(&executed(1, 100, 35000, 1));
+
+    orders.track_response(&canceled(1, 150));
+    let order = orders.get(num).unwrap();
+    assert_eq!(order.status(), OrderStatus::PartiallyFilled);
+    assert_eq!(order.leaves_quantity(), 50);
+
+    orders.track_response(&canceled(1, 50));
+    let order = orders.get(num).unwrap();
+    assert_eq!(order.status(), OrderStatus::Dead);
+    assert_eq!(order.cumulative_quantity(), 100);
+    assert_eq!(orders.open_orders().count(), 0);
+}
+
+#[test] fn order_rejected_and_dead() {
+
+    let mut orders = OrderManager::new();
+
+    orders.track_request(&enter_order(1, Side::Buy, 100));
+    orders.track_request(&enter_order(2, Side::Buy, 100));
+    orders.track_response(&rejected(1));
+    orders.track_response(&accepted(2, b'B', 100, b'D'));
+
+    let statuses: Vec<_> = orders.orders()
+        .map(|order| order.status())
+        .collect();
+    assert_eq!(statuses, vec![OrderStatus::Rejected, OrderStatus::Dead]);
+}
+
+#[test] fn order_replaced() {
+
+    let mut orders = OrderManager::new();
+
+    orders.track_request(&enter_order(1, Side::Buy, 300));
+    orders.track_response(&accepted(1, b'B', 300, b'L'));
+
+    orders.track_request(&replace!{
+        old_ref_num: user_ref_num(1),
+        new_ref_num: user_ref_num(2),
+        quantity: 200u32,
+        price: Price::new(35100).unwrap(),
+        time_in_force: TimeInForce::Day,
+        display: Display::Visible,
+        intermarket_sweep: false,
+        order_token: OrderToken::from("Replaced").unwrap()
+    });
+
+    let new = orders.get(user_ref_num(2)).unwrap();
+    assert_eq!(new.status(), OrderStatus::Pending);
+    assert_eq!(new.side(), Side::Buy);
+
+    orders.track_response(&replaced(1, 2, 200, 35100, b'L'));
+    let old = orders.get(user_ref_num(1)).unwrap();
+    let new = orders.get(user_ref_num(2)).unwrap();
+    assert_eq!(old.status(), OrderStatus::Dead);
+    assert_eq!(new.status(), OrderStatus::Live);
+    assert_eq!(new.price(), Price::new(35100).unwrap());
+}
+
+#[test] fn untrack_unsent() {
+
+    let mut orders = OrderManager::new();
+
+    orders.track_request(&enter_order(1, Side::Buy, 300));
+    orders.track_response(&accepted(1, b'B', 300, b'L'));
+
+    let replace = replace!{
+        old_ref_num: user_ref_num(1),
+        new_ref_num: user_ref_num(2),
+        quantity: 200u32,
+        price: Price::new(35100).unwrap(),
+        time_in_force: TimeInForce::Day,
+        display: Display::Visible,
+        intermarket_sweep: false,
+        order_token: OrderToken::from("Replaced").unwrap()
+    };
+    orders.track_request(&replace);
+    orders.track_request(&enter_order(3, Side::Sell, 100));
+
+    // Requests that were never sent are forgotten.
+    orders.untrack_request(&replace);
+    orders.untrack_request(&enter_order(3, Side::Sell, 100));
+    assert!(orders.get(user_ref_num(2)).is_none());
+    assert!(orders.get(user_ref_num(3)).is_none());
+    assert_eq!(orders.lineage(user_ref_num(1)).unwrap().history().len(), 1);
+    assert_eq!(orders.by_symbol(&StockSymbol::from("STONKS").unwrap())
+        .count(), 1);
+
+    // Orders the server has responded to are kept.
+    orders.untrack_request(&enter_order(1, Side::Buy, 300));
+    assert_eq!(
+        orders.get(user_ref_num(1)).unwrap().status(),
+        OrderStatus::Live
+    );
+}
+
+#[test] fn accepted_values_reindexed() {
+
+    let mut orders = OrderManager::new();
+    orders.track_request(&enter_order(1, Side::Buy, 100));
+
+    // The server may accept a different symbol and token than entered.
+    let mut data = accepted(1, b'B', 100, b'L').encode();
+    data[18..26].copy_from_slice(b"MEME    ");
+    data[48..62].copy_from_slice(b"Accepted      ");
+    orders.track_response(&response(data));
+
+    let stonks = StockSymbol::from("STONKS").unwrap();
+    let meme = StockSymbol::from("MEME").unwrap();
+    assert_eq!(orders.by_symbol(&stonks).count(), 0);
+    assert_eq!(orders.by_symbol(&meme).count(), 1);
+
+    let entered = OrderToken::from("To The Moon").unwrap();
+    let accepted = OrderToken::from("Accepted").unwrap();
+    assert_eq!(orders.by_token(&entered).count(), 0);
+    assert_eq!(orders.by_token(&accepted).next().unwrap().symbol(), meme);
+}
+
+#[test] fn order_queries() {
+
+    let mut orders = OrderManager::new();
+
+    orders.track_response(&accepted(1, b'B', 100, b'L'));
+    orders.track_response(&accepted(2, b'S', 100, b'L'));
+    orders.track_response(&replaced(2, 3, 100, 35000, b'L'));
+
+    let symbol = StockSymbol::from("STONKS").unwrap();
+    assert_eq!(orders.by_symbol(&symbol).count(), 3);
+
+    let other = StockSymbol::from("MEME").unwrap();
+    assert_eq!(orders.by_symbol(&other).count(), 0);
+
+    let token = OrderToken::from("To The Moon").unwrap();
+    let nums: Vec<u32> = orders.by_token(&token)
+        .map(|order| order.user_ref_num().val())
+        .collect();
+    assert_eq!(nums, vec![1, 2]);
+
+    let open: Vec<u32> = orders.open_orders()
+        .map(|order| order.user_ref_num().val())
+        .collect();
+    assert_eq!(open, vec![1, 3]);
+}
+
+#[test] fn replace_lineage() {
+
+    let mut orders = OrderManager::new();
+
+    orders.track_request(&enter_order(1, Side::Buy, 300));
+    orders.track_response(&accepted(1, b'B', 300, b'L'));
+    orders.track_response(&executed(1, 100, 35000, 1));
+
+    orders.track_response(&replaced(1, 2, 200, 35100, b'L'));
+    orders.track_response(&executed(2, 50, 35100, 2));
+
+    // A rejected replace does not consume the replaced order.
+    orders.track_request(&replace!{
+        old_ref_num: user_ref_num(2),
+        new_ref_num: user_ref_num(3),
+        quantity: 150u32,
+        price: Price::new(35200).unwrap(),
+        time_in_force: TimeInForce::Day,
+        display: Display::Visible,
+        intermarket_sweep: false,
+        order_token: OrderToken::from("Replaced").unwrap()
+    });
+    orders.track_response(&rejected(3));
+
+    let lineage = orders.lineage(user_ref_num(3)).unwrap();
+    assert_eq!(orders.root(user_ref_num(3)), user_ref_num(1));
+    assert_eq!(lineage.root().user_ref_num(), user_ref_num(1));
+    assert_eq!(lineage.current().user_ref_num(), user_ref_num(2));
+
+    let history: Vec<(u32, u32, u64)> = lineage.history().iter()
+        .map(|order| (
+            order.user_ref_num().val(),
+            order.quantity(),
+            order.price().val()
+        ))
+        .collect();
+    assert_eq!(history, vec![
+        (1, 300, 35000),
+        (2, 200, 35100),
+        (3, 150, 35200),
+    ]);
+
+    // Fills on the newest order roll up to the root.
+    assert_eq!(lineage.cumulative_quantity(), 150);
+    assert_eq!(lineage.leaves_quantity(), 150);
+    assert_eq!(lineage.average_price(), Some(Price::new(35033).unwrap()));
+    assert!(lineage.is_open());
+}
+
+#[test] fn replace_accepted_dead() {
+
+    let mut orders = OrderManager::new();
+
+    orders.track_request(&enter_order(1, Side::Buy, 100));
+    orders.track_response(&accepted(1, b'B', 100, b'L'));
+    orders.track_response(&canceled(1, 100));
+
+    // The original order is no longer live,
+    // so the replacement is accepted as a new (dead) order.
+    orders.track_request(&replace!{
+        old_ref_num: user_ref_num(1),
+        new_ref_num: user_ref_num(2),
+        quantity: 100u32,
+        price: Price::new(35100).unwrap(),
+        time_in_force: TimeInForce::Day,
+        display: Display::Visible,
+        intermarket_sweep: false,
+        order_token: OrderToken::from("Replaced").unwrap()
+    });
+    orders.track_response(&accepted(2, b'B', 100, b'D'));
+
+    let lineage = orders.lineage(user_ref_num(2)).unwrap();
+    assert_eq!(lineage.root().user_ref_num(), user_ref_num(1));
+    assert_eq!(lineage.current().status(), OrderStatus::Dead);
+    assert_eq!(lineage.history().len(), 2);
+    assert!(!lineage.is_open());
+}
@@ -0,0 +1,98 @@
+
+use crate::{
+    orders::OrderManager,
+    positions::PositionTracker,
+    types::*,
+};
+
+use super::orders::{ enter_order, accepted, executed, broken };
+
+
+fn stonks() -> StockSymbol {
+    StockSymbol::from("STONKS").unwrap()
+}
+
+fn price(val: u64) -> Price {
+    Price::new(val).unwrap()
+}
+
+
+#[test] fn long_position() {
+
+    let mut positions = PositionTracker::new();
+
+    positions.execute(stonks(), Side::Buy, 1, 100, price(10_0000));
+    positions.execute(stonks(), Side::Buy, 2, 100, price(12_0000));
+
+    let position = positions.position(&stonks()).unwrap();
+    assert_eq!(position.net_position(), 200);
+    assert_eq!(position.average_cost(), Some(price(11_0000)));
+    assert_eq!(position.realized_pnl(), 0);
+
+    // Selling realizes P&L against the average cost.
+    positions.execute(stonks(), Side::Sell, 3, 150, price(13_0000));
+
+    let position = positions.position(&stonks()).unwrap();
+    assert_eq!(position.net_position(), 50);
+    assert_eq!(position.average_cost(), Some(price(11_0000)));
+    assert_eq!(position.realized_pnl(), 150 * 2_0000);
+
+    // Repeated executions are only applied once.
+    assert!(positions.execute(
+        stonks(), Side::Sell, 3, 150, price(13_0000)
+    ).is_none());
+    assert_eq!(positions.realized_pnl(), 150 * 2_0000);
+}
+
+#[test] fn short_position() {
+
+    let mut positions = PositionTracker::new();
+
+    positions.execute(stonks(), Side::SellShort, 1, 100, price(20_0000));
+    positions.execute(stonks(), Side::SellShortExempt, 2, 100, price(18_0000));
+
+    let position = positions.position(&stonks()).unwrap();
+    assert_eq!(position.net_position(), -200);
+    assert_eq!(position.average_cost(), Some(price(19_0000)));
+
+    // Buying through a short position closes it and opens a long one.
+    positions.execute(stonks(), Side::Buy, 3, 300, price(17_0000));
+
+    let position = positions.position(&stonks()).unwrap();
+    assert_eq!(position.net_position(), 100);
+    assert_eq!(position.average_cost(), Some(price(17_0000)));
+    assert_eq!(position.realized_pnl(), 200 * 2_0000);
+}
+
+#[test] fn broken_trade() {
+
+    let mut orders = OrderManager::new();
+    let mut positions = PositionTracker::new();
+
+    orders.track_request(&enter_order(1, Side::Buy, 300));
+    orders.track_response(&accepted(1, b'B', 300, b'L'));
+    orders.track_request(&enter_order(2, Side::Sell, 300));
+    orders.track_response(&accepted(2, b'S', 300, b'L'));
+
+    for response in [
+        executed(1, 200, 10_0000, 1),
+        executed(2, 100, 11_0000, 2),
+        executed(2, 100, 12_0000, 3),
+    ] {
+        orders.track_response(&response);
+        positions.track_response(&orders, &response);
+    }
+
+    let position = positions.position(&stonks()).unwrap();
+    assert_eq!(position.net_position(), 0);
+    assert_eq!(position.average_cost(), None);
+    assert_eq!(position.realized_pnl(), 100 * 1_0000 + 100 * 2_0000);
+
+    // The broken execution is reversed as if it never happened.
+    positions.track_response(&orders, &broken(2, 2));
+
+    let position = positions.position(&stonks()).unwrap();
+    assert_eq!(position.net_position(), 100);
+    assert_eq!(position.average_cost(), Some(price(10_0000)));
+    assert_eq!(position.realized_pnl(), 100 * 2_0000);
+}
@@ -1,7 +1,8 @@
 
 use crate::{ 
     enter, 
-    msg::{ TagValue, OuchRequest },
+    msg::{ TagValue, OuchRequest, EnterOrder },
+    error::BadElementError,
 };
 
 use crate::types::*;
@@ -35,7 +36,7 @@ use crate::types::*;
     assert_eq!(eo.time_in_force(), TimeInForce::Day);
     assert_eq!(eo.display(), Display::Visible);
     assert_eq!(eo.capacity(), Capacity::Agency);
-    assert_eq!(eo.intermarket_sweep(), false);
+    assert!(!eo.intermarket_sweep());
     assert_eq!(eo.cross_type(), CrossType::Opening);
     assert_eq!(eo.order_token(), OrderToken::from("OrderToken").unwrap());
     assert!(request.options().is_empty());
@@ -150,3 +151,91 @@ fn bad_quantity() {
 }
 
 
+#[test] fn build_enter() {
+
+    let request = EnterOrder::builder()
+        .user_ref_num(UserRefNum::new())
+        .side(Side::Buy)
+        .quantity(500u32)
+        .symbol(StockSymbol::from("STONKS").unwrap())
+        .price(Price::new(35000u64).unwrap())
+        .max_floor(100u32)
+        .post_only()
+        .peg(PriceType::MidpointPeg, SignedPrice::new(-100i32).unwrap())
+        .expire_after(ElapsedTime::new(60u32).unwrap())
+        .build()
+        .expect("Should be a good order");
+
+    let eo = match request {
+        OuchRequest::EnterOrder(ref eo) => eo,
+        _ => panic!{"Damn, son, where'd you find this?"}
+    };
+    assert_eq!(eo.time_in_force(), TimeInForce::GoodTilTime);
+    assert_eq!(eo.display(), Display::Visible);
+    assert_eq!(eo.capacity(), Capacity::Agency);
+    assert!(!eo.intermarket_sweep());
+    assert_eq!(eo.cross_type(), CrossType::ContinuousMarket);
+    assert_eq!(eo.order_token(), OrderToken::from("").unwrap());
+    assert_eq!(eo.options().len(), 5);
+    assert!(eo.options().contains(&TagValue::MaxFloor(100u32)));
+    assert!(eo.options().contains(&TagValue::PostOnly(true)));
+    assert!(eo.options().contains(
+        &TagValue::ExpireTime(ElapsedTime::new(60u32).unwrap())
+    ));
+}
+
+#[test] fn build_enter_errors() {
+
+    let builder = EnterOrder::builder()
+        .user_ref_num(UserRefNum::new())
+        .side(Side::SellShort)
+        .quantity(500u32)
+        .symbol(StockSymbol::from("STONKS").unwrap());
+
+    assert!(matches!(
+        builder.clone().build(),
+        Err(BadElementError::MissingField(field)) if field == "price"
+    ));
+
+    let builder = builder.price(Price::new(35000u64).unwrap());
+    assert!(matches!(
+        builder.clone().build(),
+        Err(BadElementError::MissingLocate)
+    ));
+    assert!(matches!(
+        builder.clone().max_floor(600u32).build(),
+        Err(BadElementError::InvalidMaxFloor { max_floor: 600, quantity: 500 })
+    ));
+
+    builder.locate(Mpid::from("LOCT").unwrap())
+        .build()
+        .expect("Should be a good order");
+}
+
+#[test] fn enter_options() {
+
+    let request = EnterOrder::builder()
+        .user_ref_num(UserRefNum::new())
+        .side(Side::Buy)
+        .quantity(500u32)
+        .symbol(StockSymbol::from("STONKS").unwrap())
+        .price(Price::new(35000u64).unwrap())
+        .min_qty(100u32)
+        .post_only()
+        .expire_after(ElapsedTime::new(60u32).unwrap())
+        .build()
+        .expect("Should be a good order");
+
+    let mut eo = match request {
+        OuchRequest::EnterOrder(eo) => eo,
+        _ => panic!{"Damn, son, where'd you find this?"}
+    };
+    assert_eq!(eo.min_qty(), Some(100u32));
+    assert_eq!(eo.post_only(), Some(true));
+    assert_eq!(eo.expire_time(), Some(ElapsedTime::new(60u32).unwrap()));
+    assert_eq!(eo.max_floor(), None);
+    assert_eq!(eo.firm(), None);
+
+    // Options not allowed on the message are still refused.
+    assert!(eo.add_option(TagValue::DisplayQuantity(100u32)).is_err());
+}
@@ -5,4 +5,5 @@
 #[cfg(test)] mod permission;
 #[cfg(test)] mod enter;
 #[cfg(test)] mod replace;
-
+#[cfg(test)] mod validate;
+#[cfg(test)] mod parse;
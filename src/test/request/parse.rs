@@ -0,0 +1,241 @@
+
+use crate::{
+    enter,
+    replace,
+    modify,
+    cancel,
+    mass_cancel,
+    disable_entry,
+    enable_entry,
+    account_query,
+    msg::{ TagValue, OuchRequest, OuchRequestKind },
+    error::ParseErrorKind,
+};
+
+use nom::error::ErrorKind;
+
+use crate::types::*;
+
+
+// Every request should parse back to the request that was encoded.
+fn round_trip(request: OuchRequest) {
+
+    let bytes = request.encode();
+    let (rest, parsed) = OuchRequest::parse(&bytes)
+        .expect("Should be valid data");
+
+    assert!(rest.is_empty());
+    assert_eq!(parsed, request);
+}
+
+#[test] fn parse_requests() {
+
+    let mut request = enter!{
+        user_ref_num: UserRefNum::new(),
+        side: Side::Buy,
+        quantity: 500u32,
+        symbol: StockSymbol::from("STONKS").unwrap(),
+        price: Price::new(35001u64).unwrap(),
+        time_in_force: TimeInForce::Day,
+        display: Display::Visible,
+        capacity: Capacity::Agency,
+        intermarket_sweep: false,
+        cross_type: CrossType::ContinuousMarket,
+        order_token: OrderToken::from("OrderToken").unwrap()
+    };
+    request.add_option(TagValue::MaxFloor(100)).unwrap();
+    request.add_option(TagValue::Firm(Mpid::from("FIRM").unwrap())).unwrap();
+    request.add_option(TagValue::PostOnly(true)).unwrap();
+    request.add_option(TagValue::UserRefIndex(3)).unwrap();
+    round_trip(request);
+
+    let mut request = replace!{
+        old_ref_num: UserRefNum::new(),
+        new_ref_num: UserRefNum::new(),
+        quantity: 100u32,
+        price: Price::new(35001u64).unwrap(),
+        time_in_force: TimeInForce::Day,
+        display: Display::Hidden,
+        intermarket_sweep: true,
+        order_token: OrderToken::from("OrderToken").unwrap()
+    };
+    request.add_option(TagValue::MinQty(100)).unwrap();
+    request.add_option(TagValue::Side(Side::Sell)).unwrap();
+    round_trip(request);
+
+    let mut request = modify!{
+        user_ref_num: UserRefNum::new(),
+        side: Side::SellShort,
+        quantity: 100u32,
+    };
+    request.add_option(TagValue::SharesLocated(true)).unwrap();
+    request.add_option(TagValue::LocateBroker(Mpid::from("LOCT").unwrap()))
+        .unwrap();
+    round_trip(request);
+
+    round_trip(cancel!{
+        user_ref_num: UserRefNum::new(),
+        quantity: 0u32,
+    });
+
+    round_trip(mass_cancel!{
+        user_ref_num: UserRefNum::new(),
+        firm: Mpid::from("FIRM").unwrap(),
+        symbol: StockSymbol::from("STONKS").unwrap(),
+    });
+
+    round_trip(disable_entry!{
+        user_ref_num: UserRefNum::new(),
+        firm: Mpid::from("FIRM").unwrap(),
+    });
+
+    round_trip(enable_entry!{
+        user_ref_num: UserRefNum::new(),
+        firm: Mpid::from("FIRM").unwrap(),
+    });
+
+    round_trip(account_query!());
+}
+
+#[test] fn parse_bad_request() {
+
+    // Unrecognized type tag.
+    let err = OuchRequest::parse(b"Z").unwrap_err();
+    assert!(!err.is_incomplete());
+    assert_eq!(err.field(), Some("message type"));
+
+    // Truncated message.
+    let bytes = cancel!{
+        user_ref_num: UserRefNum::new(),
+        quantity: 0u32,
+    }.encode();
+    let err = OuchRequest::parse(&bytes[..4]).unwrap_err();
+    assert!(err.is_incomplete());
+    assert_eq!(err.message_type(), Some('X'));
+    assert_eq!(err.field(), Some("user_ref_num"));
+    assert_eq!(err.offset(), 1);
+    assert_eq!(err.bytes(), &bytes[..4]);
+
+    // Invalid side, after the tag and UserRefNum.
+    let mut bytes = modify!{
+        user_ref_num: UserRefNum::new(),
+        side: Side::Buy,
+        quantity: 0u32,
+    }.encode();
+    bytes[5] = b'?';
+    let err = OuchRequest::parse(&bytes).unwrap_err();
+    assert!(!err.is_incomplete());
+    assert_eq!(err.message_type(), Some('M'));
+    assert_eq!(err.field(), Some("side"));
+    assert_eq!(err.offset(), 5);
+    assert_eq!(
+        err.to_string(),
+        "invalid (Tag) field side in 'M' message at byte 5"
+    );
+}
+
+#[test] fn request_kind() {
+
+    let request = account_query!();
+    assert_eq!(request.kind(), OuchRequestKind::AccountQuery);
+    assert_eq!(request.to_string(), "Q");
+}
+
+#[test] fn parse_back_to_back() {
+
+    let mut first = cancel!{
+        user_ref_num: UserRefNum::new(),
+        quantity: 0u32,
+    };
+    first.add_option(TagValue::UserRefIndex(1)).unwrap();
+    let second = account_query!();
+
+    let mut bytes = first.encode();
+    bytes.extend(second.encode());
+
+    let (rest, parsed) = OuchRequest::parse(&bytes).unwrap();
+    assert_eq!(parsed, first);
+    let (rest, parsed) = OuchRequest::parse(rest).unwrap();
+    assert_eq!(parsed, second);
+    assert!(rest.is_empty());
+}
+
+#[test] fn parse_bad_appendage() {
+
+    let mut request = cancel!{
+        user_ref_num: UserRefNum::new(),
+        quantity: 0u32,
+    };
+    request.add_option(TagValue::UserRefIndex(1)).unwrap();
+    let bytes = request.encode();
+
+    // Type tag, UserRefNum and quantity come before the appendage.
+    let appendage = 9;
+
+    // Appendage is cut short.
+    let err = OuchRequest::parse(&bytes[..bytes.len() - 1]).unwrap_err();
+    assert!(err.is_incomplete());
+    assert_eq!(err.field(), Some("options"));
+    assert_eq!(err.offset(), appendage + 2);
+
+    // Option runs past the end of the appendage.
+    let mut bad = bytes.clone();
+    bad[appendage + 2] = 3;
+    bad.push(0);
+    let err = OuchRequest::parse(&bad).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        ParseErrorKind::Invalid(ErrorKind::LengthValue)
+    );
+    assert_eq!(err.field(), Some("options"));
+    assert_eq!(err.offset(), appendage + 2);
+
+    // Option value is longer than its tag allows.
+    let mut bad = bytes.clone();
+    bad[appendage + 1] = 4;
+    bad[appendage + 2] = 3;
+    bad.push(0);
+    let err = OuchRequest::parse(&bad).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        ParseErrorKind::Invalid(ErrorKind::LengthValue)
+    );
+    assert_eq!(err.field(), Some("UserRefIndex"));
+
+    // Option value is shorter than its tag requires.
+    let mut bad = bytes.clone();
+    bad[appendage + 1] = 2;
+    bad[appendage + 2] = 1;
+    bad.pop();
+    let err = OuchRequest::parse(&bad).unwrap_err();
+    assert_eq!(err.kind(), ParseErrorKind::Invalid(ErrorKind::Complete));
+    assert_eq!(err.field(), Some("UserRefIndex"));
+}
+
+#[test] fn parse_missing_appendage() {
+
+    // Modify and Account Query requests may leave out the appendage.
+    let modify = modify!{
+        user_ref_num: UserRefNum::new(),
+        side: Side::Buy,
+        quantity: 0u32,
+    };
+    let bytes = modify.encode();
+    let (rest, parsed) = OuchRequest::parse(&bytes[..bytes.len() - 2])
+        .unwrap();
+    assert_eq!(parsed, modify);
+    assert!(rest.is_empty());
+
+    let (rest, parsed) = OuchRequest::parse(b"Q").unwrap();
+    assert_eq!(parsed, account_query!());
+    assert!(rest.is_empty());
+
+    // Other requests have not arrived in full without it.
+    let bytes = cancel!{
+        user_ref_num: UserRefNum::new(),
+        quantity: 0u32,
+    }.encode();
+    let err = OuchRequest::parse(&bytes[..bytes.len() - 2]).unwrap_err();
+    assert!(err.is_incomplete());
+    assert_eq!(err.field(), Some("options length"));
+}
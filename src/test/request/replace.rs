@@ -30,7 +30,7 @@ use crate::types::*;
     assert_eq!(eo.price(), Price::new(35001u64).unwrap());
     assert_eq!(eo.time_in_force(), TimeInForce::Day);
     assert_eq!(eo.display(), Display::Visible);
-    assert_eq!(eo.intermarket_sweep(), false);
+    assert!(!eo.intermarket_sweep());
     assert_eq!(eo.order_token(), OrderToken::from("OrderToken").unwrap());
     assert!(request.options().is_empty());
 
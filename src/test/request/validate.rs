@@ -0,0 +1,172 @@
+
+use crate::{
+    enter,
+    replace,
+    modify,
+    cancel,
+    error::BadElementError,
+    msg::{ TagValue, OuchRequest },
+};
+
+use crate::types::*;
+
+
+fn order(time_in_force: TimeInForce, cross_type: CrossType) -> OuchRequest {
+    enter!{
+        user_ref_num: UserRefNum::new(),
+        side: Side::Buy,
+        quantity: 500u32,
+        symbol: StockSymbol::from("STONKS").unwrap(),
+        price: Price::new(35001u64).unwrap(),
+        time_in_force: time_in_force,
+        display: Display::Visible,
+        capacity: Capacity::Agency,
+        intermarket_sweep: false,
+        cross_type: cross_type,
+        order_token: OrderToken::from("OrderToken").unwrap()
+    }
+}
+
+fn day_order() -> OuchRequest {
+    order(TimeInForce::Day, CrossType::ContinuousMarket)
+}
+
+#[test] fn validate_expire_time() {
+
+    let mut request = order(
+        TimeInForce::GoodTilTime,
+        CrossType::ContinuousMarket
+    );
+    assert!(matches!(
+        request.validate(),
+        Err(BadElementError::MissingExpireTime)
+    ));
+
+    request.add_option(
+        TagValue::ExpireTime(ElapsedTime::new(60).unwrap())
+    ).unwrap();
+    assert!(request.validate().is_ok());
+}
+
+#[test] fn validate_handle_inst() {
+
+    let mut request = day_order();
+    request.add_option(
+        TagValue::HandleInst(HandleInst::ImbalanceOnly)
+    ).unwrap();
+    assert!(matches!(
+        request.validate(),
+        Err(BadElementError::InvalidHandleInst(
+            HandleInst::ImbalanceOnly,
+            CrossType::ContinuousMarket
+        ))
+    ));
+
+    let mut request = order(TimeInForce::Day, CrossType::Closing);
+    request.add_option(
+        TagValue::HandleInst(HandleInst::ImbalanceOnly)
+    ).unwrap();
+    assert!(request.validate().is_ok());
+
+    let mut request = order(TimeInForce::Day, CrossType::Retail);
+    request.add_option(
+        TagValue::HandleInst(HandleInst::ExtendedContinuous)
+    ).unwrap();
+    assert!(request.validate().is_err());
+}
+
+#[test] fn validate_reserves() {
+
+    let mut request = day_order();
+    request.add_option(TagValue::MaxFloor(600)).unwrap();
+    assert!(matches!(
+        request.validate(),
+        Err(BadElementError::InvalidMaxFloor { max_floor: 600, quantity: 500 })
+    ));
+
+    request.add_option(TagValue::MaxFloor(100)).unwrap();
+    request.add_option(TagValue::MinQty(150)).unwrap();
+    assert!(matches!(
+        request.validate(),
+        Err(BadElementError::InvalidMinQty(150))
+    ));
+
+    request.add_option(TagValue::MinQty(200)).unwrap();
+    assert!(request.validate().is_ok());
+}
+
+#[test] fn validate_peg_offset() {
+
+    let offset = SignedPrice::new(-100).unwrap();
+
+    let mut request = day_order();
+    request.add_option(TagValue::PegOffset(offset)).unwrap();
+    assert!(matches!(
+        request.validate(),
+        Err(BadElementError::UnpeggedOffset(..))
+    ));
+
+    request.add_option(TagValue::PriceType(PriceType::Midpoint)).unwrap();
+    assert!(request.validate().is_err());
+
+    request.add_option(TagValue::PriceType(PriceType::PrimaryPeg)).unwrap();
+    assert!(request.validate().is_ok());
+
+    request.add_option(TagValue::DiscretionPegOffset(offset)).unwrap();
+    assert!(request.validate().is_err());
+
+    request.add_option(
+        TagValue::DiscretionPriceType(PriceType::MarketPeg)
+    ).unwrap();
+    assert!(request.validate().is_ok());
+}
+
+#[test] fn validate_locate() {
+
+    let mut request = modify!{
+        user_ref_num: UserRefNum::new(),
+        side: Side::SellShort,
+        quantity: 100u32,
+    };
+    assert!(matches!(
+        request.validate(),
+        Err(BadElementError::MissingLocate)
+    ));
+
+    request.add_option(TagValue::SharesLocated(false)).unwrap();
+    assert!(request.validate().is_err());
+
+    request.add_option(
+        TagValue::LocateBroker(Mpid::from("LOCT").unwrap())
+    ).unwrap();
+    assert!(request.validate().is_ok());
+
+    // Replacements only change side if it is given.
+    let mut request = replace!{
+        old_ref_num: UserRefNum::new(),
+        new_ref_num: UserRefNum::new(),
+        quantity: 100u32,
+        price: Price::new(35001u64).unwrap(),
+        time_in_force: TimeInForce::Day,
+        display: Display::Visible,
+        intermarket_sweep: false,
+        order_token: OrderToken::from("OrderToken").unwrap()
+    };
+    assert!(request.validate().is_ok());
+
+    request.add_option(TagValue::Side(Side::SellShort)).unwrap();
+    assert!(matches!(
+        request.validate(),
+        Err(BadElementError::MissingLocate)
+    ));
+
+    request.add_option(TagValue::SharesLocated(true)).unwrap();
+    assert!(request.validate().is_ok());
+
+    // Other requests are always valid.
+    let request = cancel!{
+        user_ref_num: UserRefNum::new(),
+        quantity: 0u32,
+    };
+    assert!(request.validate().is_ok());
+}
@@ -63,7 +63,7 @@ use nsdq_util::types::time::Timelike;
     assert_eq!(accepted.display(), Display::Visible);
     assert_eq!(accepted.order_ref_num(), 1u64);
     assert_eq!(accepted.capacity(), Capacity::Agency);
-    assert_eq!(accepted.intermarket_sweep(), false);
+    assert!(!accepted.intermarket_sweep());
     assert_eq!(accepted.cross_type(), CrossType::Opening);
     assert_eq!(accepted.order_state(), OrderState::Live);
     assert_eq!(accepted.order_token().to_string(), String::from("To The Moon"));
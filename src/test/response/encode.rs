@@ -0,0 +1,219 @@
+
+use crate::msg::*;
+use crate::types::*;
+use crate::error::{ OuchError, BadElementError };
+
+
+// Every response should parse back to the response that was encoded.
+fn round_trip(response: OuchResponse) {
+
+    let bytes = response.encode();
+    let (rest, parsed) = OuchResponse::parse(&bytes)
+        .expect("Should be valid data");
+
+    assert!(rest.is_empty());
+    assert_eq!(parsed, response);
+}
+
+fn time() -> NaiveTime {
+    NaiveTime::from_hms_nano_opt(9, 30, 0, 123).unwrap()
+}
+
+fn num(n: u32) -> UserRefNum {
+    let (_, num) = UserRefNum::parse(&n.to_be_bytes())
+        .expect("Should be valid data");
+    num
+}
+
+fn price() -> Price { Price::new(35001).unwrap() }
+fn symbol() -> StockSymbol { StockSymbol::from("STONKS").unwrap() }
+fn firm() -> Mpid { Mpid::from("FIRM").unwrap() }
+fn token() -> OrderToken { OrderToken::from("To The Moon").unwrap() }
+
+#[test] fn encode_system_event() {
+
+    let event = SystemEvent::new(time(), EventCode::EndOfDay);
+    let bytes = OuchResponse::SystemEvent(event.clone()).encode();
+
+    // The timestamp is encoded ahead of the event code.
+    assert_eq!(bytes.len(), 10);
+    assert_eq!(bytes[9], b'E');
+    round_trip(OuchResponse::SystemEvent(event));
+}
+
+#[test] fn encode_responses() {
+
+    use OuchResponse::*;
+
+    round_trip(AccountQueryResponse(
+        crate::msg::AccountQueryResponse::new(time(), num(7))
+    ));
+
+    round_trip(OrderPriorityUpdate(crate::msg::OrderPriorityUpdate::new(
+        time(), num(1), price(), Display::Hidden, 42
+    )));
+
+    let mut restated = crate::msg::OrderRestated::new(
+        time(), num(1), RestateReason::Refresh
+    );
+    restated.add_option(TagValue::DisplayQuantity(100)).unwrap();
+    round_trip(OrderRestated(restated));
+
+    round_trip(BrokenTrade(crate::msg::BrokenTrade::new(
+        time(), num(1), 99, BrokenReason::Consent, token()
+    )));
+
+    round_trip(DisableOrderEntryResponse(
+        crate::msg::DisableOrderEntryResponse::new(time(), num(1), firm())
+    ));
+    round_trip(EnableOrderEntryResponse(
+        crate::msg::EnableOrderEntryResponse::new(time(), num(1), firm())
+    ));
+
+    round_trip(OrderExecuted(crate::msg::OrderExecuted::new(
+        time(), num(1), 100, price(), Liquidity::Added, 99
+    )));
+
+    let mut accepted = crate::msg::OrderAccepted::new(
+        time(),
+        num(1),
+        Side::Buy,
+        100,
+        symbol(),
+        price(),
+        TimeInForce::Day,
+        Display::Visible,
+        42,
+        Capacity::Agency,
+        false,
+        CrossType::ContinuousMarket,
+        OrderState::Live,
+        token(),
+    );
+    accepted.add_option(TagValue::SecondaryOrdRefNum(7)).unwrap();
+    accepted.add_option(TagValue::Firm(firm())).unwrap();
+    accepted.add_option(TagValue::MaxFloor(100)).unwrap();
+    accepted.add_option(TagValue::UserRefIndex(1)).unwrap();
+    round_trip(OrderAccepted(accepted));
+
+    round_trip(OrderModified(crate::msg::OrderModified::new(
+        time(), num(1), Side::Sell, 50
+    )));
+
+    round_trip(OrderCanceled(crate::msg::OrderCanceled::new(
+        time(), num(1), 100, CancelReason::UserRequested
+    )));
+
+    round_trip(OrderRejected(crate::msg::OrderRejected::new(
+        time(), num(1), RejectReason::FatFinger, token()
+    )));
+
+    round_trip(OrderReplaced(crate::msg::OrderReplaced::new(
+        time(),
+        num(1),
+        num(2),
+        Side::Buy,
+        100,
+        symbol(),
+        price(),
+        TimeInForce::Day,
+        Display::Visible,
+        43,
+        Capacity::Agency,
+        false,
+        CrossType::ContinuousMarket,
+        OrderState::Dead,
+        token(),
+    )));
+
+    round_trip(AiqCanceled(crate::msg::AiqCanceled::new(
+        time(),
+        num(1),
+        100,
+        CancelReason::SelfMatchPrevention,
+        100,
+        price(),
+        Liquidity::Removed,
+        AiqStrategy::CancelOldestMpid,
+    )));
+
+    round_trip(CancelPending(crate::msg::CancelPending::new(time(), num(1))));
+    round_trip(CancelRejected(crate::msg::CancelRejected::new(time(), num(1))));
+
+    round_trip(MassCancelResponse(crate::msg::MassCancelResponse::new(
+        time(), num(1), firm(), symbol()
+    )));
+}
+
+#[test] fn encode_long_appendage() {
+
+    // Enough options that the appendage is longer than 255 bytes.
+    let mut canceled = crate::msg::OrderCanceled::new(
+        time(), num(1), 100, CancelReason::UserRequested
+    );
+    for tag in 100u8..200 {
+        let option = TagValue::Unknown { tag, bytes: vec![tag, 0] };
+        canceled.add_option(option).unwrap();
+    }
+
+    let response = OuchResponse::OrderCanceled(canceled);
+    let bytes = response.encode();
+    assert!(bytes.len() > 400);
+    round_trip(response.clone());
+
+    // Messages can be parsed back-to-back from one buffer.
+    let mut buffer = bytes.clone();
+    buffer.extend(&bytes);
+    let (rest, first) = OuchResponse::parse(&buffer).unwrap();
+    let (rest, second) = OuchResponse::parse(rest).unwrap();
+    assert_eq!(first, response);
+    assert_eq!(second, response);
+    assert!(rest.is_empty());
+}
+
+#[test] fn encode_unknown_option_length() {
+
+    let mut canceled = crate::msg::OrderCanceled::new(
+        time(), num(1), 100, CancelReason::UserRequested
+    );
+
+    // The length marker counts the tag, so 254 bytes is the longest value.
+    let option = TagValue::Unknown { tag: 99u8, bytes: vec![7u8; 254] };
+    canceled.add_option(option.clone()).unwrap();
+    round_trip(OuchResponse::OrderCanceled(canceled.clone()));
+
+    let too_long = TagValue::Unknown { tag: 99u8, bytes: vec![7u8; 255] };
+    match canceled.add_option(too_long) {
+        Err(OuchError::InvalidElement(BadElementError::OptionTooLong {
+            tag: 99u8,
+            length: 255
+        })) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    assert_eq!(canceled.options(), &vec![option]);
+}
+
+#[test] fn encode_unknown_option_known_tag() {
+
+    let mut canceled = crate::msg::OrderCanceled::new(
+        time(), num(1), 100, CancelReason::UserRequested
+    );
+    canceled.add_option(TagValue::UserRefIndex(1)).unwrap();
+
+    // Firm (tag 2) would be encoded from these bytes, but not parsed back.
+    let option = TagValue::Unknown { tag: 2u8, bytes: vec![1, 2, 3] };
+    match canceled.add_option(option) {
+        Err(OuchError::InvalidElement(BadElementError::KnownOptionTag {
+            tag: 2u8,
+            ..
+        })) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+
+    // Nor may it replace a known option.
+    let option = TagValue::Unknown { tag: 28u8, bytes: vec![2] };
+    assert!(canceled.add_option(option).is_err());
+
+    assert_eq!(canceled.options(), &vec![TagValue::UserRefIndex(1)]);
+    round_trip(OuchResponse::OrderCanceled(canceled));
+}
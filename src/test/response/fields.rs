@@ -0,0 +1,93 @@
+
+use crate::msg::*;
+use crate::types::*;
+
+
+fn time() -> NaiveTime {
+    NaiveTime::from_hms_nano_opt(9, 30, 0, 123).unwrap()
+}
+
+fn num(n: u32) -> UserRefNum {
+    let (_, num) = UserRefNum::parse(&n.to_be_bytes())
+        .expect("Should be valid data");
+    num
+}
+
+#[test] fn common_fields() {
+
+    let accepted = OuchResponse::OrderAccepted(OrderAccepted::new(
+        time(),
+        num(1),
+        Side::Buy,
+        100,
+        StockSymbol::from("STONKS").unwrap(),
+        Price::new(35001).unwrap(),
+        TimeInForce::Day,
+        Display::Visible,
+        42,
+        Capacity::Agency,
+        false,
+        CrossType::ContinuousMarket,
+        OrderState::Live,
+        OrderToken::from("To The Moon").unwrap(),
+    ));
+
+    assert_eq!(accepted.kind(), OuchResponseKind::OrderAccepted);
+    assert_eq!(accepted.to_string(), "A");
+    assert_eq!(accepted.user_ref_num(), Some(num(1)));
+    assert_eq!(accepted.timestamp(), Some(time()));
+    assert_eq!(accepted.side(), Some(Side::Buy));
+    assert_eq!(accepted.quantity(), Some(100));
+    assert_eq!(accepted.symbol(), StockSymbol::from("STONKS").ok());
+    assert_eq!(accepted.price(), Price::new(35001).ok());
+    assert_eq!(
+        accepted.order_token(),
+        OrderToken::from("To The Moon").ok()
+    );
+    assert_eq!(accepted.match_number(), None);
+
+    let executed = OuchResponse::OrderExecuted(OrderExecuted::new(
+        time(), num(1), 40, Price::new(35000).unwrap(), Liquidity::Added, 99
+    ));
+
+    assert_eq!(executed.kind(), OuchResponseKind::OrderExecuted);
+    assert_eq!(executed.quantity(), Some(40));
+    assert_eq!(executed.match_number(), Some(99));
+    assert_eq!(executed.symbol(), None);
+    assert_eq!(executed.order_token(), None);
+}
+
+#[test] fn replaced_and_unknown_fields() {
+
+    let replaced = OuchResponse::OrderReplaced(OrderReplaced::new(
+        time(),
+        num(1),
+        num(2),
+        Side::Sell,
+        100,
+        StockSymbol::from("STONKS").unwrap(),
+        Price::new(35001).unwrap(),
+        TimeInForce::Day,
+        Display::Visible,
+        43,
+        Capacity::Agency,
+        false,
+        CrossType::ContinuousMarket,
+        OrderState::Live,
+        OrderToken::from("To The Moon").unwrap(),
+    ));
+
+    // The new order is the one that lives on.
+    assert_eq!(replaced.user_ref_num(), Some(num(2)));
+
+    let query = OuchResponse::AccountQueryResponse(
+        AccountQueryResponse::new(time(), num(7))
+    );
+    assert_eq!(query.user_ref_num(), None);
+    assert_eq!(query.timestamp(), Some(time()));
+
+    let unknown = OuchResponse::Unknown('Z', vec![1, 2, 3]);
+    assert_eq!(unknown.kind(), OuchResponseKind::Unknown);
+    assert_eq!(unknown.to_string(), "Z");
+    assert_eq!(unknown.timestamp(), None);
+}
@@ -11,3 +11,5 @@
 #[cfg(test)] mod replaced;
 #[cfg(test)] mod updated;
 
+#[cfg(test)] mod encode;
+#[cfg(test)] mod fields;
@@ -66,7 +66,7 @@ use nsdq_util::types::time::Timelike;
     assert_eq!(replaced.display(), Display::Visible);
     assert_eq!(replaced.order_ref_num(), 1u64);
     assert_eq!(replaced.capacity(), Capacity::Agency);
-    assert_eq!(replaced.intermarket_sweep(), false);
+    assert!(!replaced.intermarket_sweep());
     assert_eq!(replaced.cross_type(), CrossType::Opening);
     assert_eq!(replaced.order_state(), OrderState::Live);
     assert_eq!(replaced.order_token().to_string(), String::from("To The Moon"));
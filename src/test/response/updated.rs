@@ -1,5 +1,6 @@
 
 use crate::msg::{ OuchResponse, TagValue };
+use crate::error::{ OuchError, BadElementError };
 use crate::types::*;
 
 use nsdq_util::types::time::Timelike;
@@ -89,3 +90,85 @@ use nsdq_util::types::time::Timelike;
     };
 }
 
+
+#[test] fn restated_options() {
+
+    let mut restated = crate::msg::OrderRestated::new(
+        NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+        UserRefNum::new(),
+        RestateReason::Refresh
+    );
+    assert_eq!(restated.display_quantity(), None);
+
+    restated.add_option(TagValue::DisplayQuantity(100u32)).unwrap();
+    let price = Price::new(35001).unwrap();
+    restated.add_option(TagValue::DisplayPrice(price)).unwrap();
+
+    assert_eq!(restated.display_quantity(), Some(100u32));
+    assert_eq!(restated.display_price(), Some(Price::new(35001).unwrap()));
+    assert_eq!(restated.secondary_ord_ref_num(), None);
+
+    // Options that the message type does not allow are refused.
+    assert!(restated.add_option(TagValue::MaxFloor(100u32)).is_err());
+    assert_eq!(restated.options().len(), 2);
+}
+
+#[test] fn restated_unknown_option() {
+
+    let mut data: Vec<u8> = vec![b'R'];
+    // u64 for timestamp
+    data.extend(1u64.to_be_bytes());
+    // u32 for UserRefNum
+    data.extend(1u32.to_be_bytes());
+    // Reason
+    data.push(b'R');
+    // Include the appendage length marker
+    data.extend(4u16.to_be_bytes());
+    // Include the tag value length marker
+    data.push(3u8);
+    // Include an option tag that is not in the spec
+    data.push(99u8);
+    // Include the value of the unknown option
+    data.extend([1u8, 2]);
+
+    let (_data, response) = OuchResponse::parse(&data)
+        .expect("Unknown options should be kept");
+
+    let restated = match response {
+        OuchResponse::OrderRestated(ref restated) => restated,
+        _ => panic!("Damn, son, where'd you find this?")
+    };
+    assert_eq!(
+        restated.options(),
+        &vec![TagValue::Unknown { tag: 99u8, bytes: vec![1, 2] }]
+    );
+
+    // Unknown options only replace options with the same tag.
+    let mut other = restated.clone();
+    let option = TagValue::Unknown { tag: 98u8, bytes: vec![] };
+    other.add_option(option).unwrap();
+    assert_eq!(other.options().len(), 2);
+
+    // An unknown option may not take the tag of a known one,
+    // which it would replace (and be parsed back as).
+    other.add_option(TagValue::DisplayQuantity(100u32)).unwrap();
+    let option = TagValue::Unknown { tag: 22u8, bytes: vec![1, 2, 3] };
+    match other.add_option(option) {
+        Err(OuchError::InvalidElement(BadElementError::KnownOptionTag {
+            tag: 22u8,
+            ..
+        })) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    assert!(other.options().contains(&TagValue::DisplayQuantity(100u32)));
+
+    // Encode exactly as received.
+    assert_eq!(response.encode(), data);
+
+    let err = OuchResponse::parse_strict(&data)
+        .expect_err("Unknown options should be refused");
+    assert_eq!(err.message_type(), Some('R'));
+    assert_eq!(err.field(), Some("Unknown"));
+    // At the option's length marker, after the appendage length.
+    assert_eq!(err.offset(), 16);
+}
@@ -0,0 +1,166 @@
+
+use std::sync::{ Arc, Mutex };
+
+use crate::{
+    cancel,
+    replace,
+    risk::RiskGate,
+    error::RiskViolation,
+    types::*,
+};
+
+use super::orders::{
+    user_ref_num,
+    enter_order,
+    response,
+    accepted,
+    canceled,
+};
+
+
+fn stonks() -> StockSymbol {
+    StockSymbol::from("STONKS").unwrap()
+}
+
+
+#[test] fn order_limits() {
+
+    let mut gate = RiskGate::new();
+    gate.set_max_quantity(500);
+    gate.set_max_notional(400 * 3_5000);
+    gate.set_price_collar(500);
+    gate.set_reference_price(stonks(), Price::new(3_2000).unwrap());
+
+    let refused = Arc::new(Mutex::new(Vec::new()));
+    let log = refused.clone();
+    gate.on_rejection(move |_request, violation| {
+        log.lock().unwrap().push(violation.clone())
+    });
+
+    // The test orders are priced at $3.50, about 9% above the reference.
+    assert_eq!(
+        gate.check(&enter_order(1, Side::Buy, 100)),
+        Err(RiskViolation::PriceCollar { price: 3_5000, reference: 3_2000 })
+    );
+
+    gate.set_price_collar(1_000);
+    assert!(gate.check(&enter_order(1, Side::Buy, 100)).is_ok());
+
+    assert_eq!(
+        gate.check(&enter_order(2, Side::Buy, 600)),
+        Err(RiskViolation::MaxQuantity { quantity: 600, limit: 500 })
+    );
+    assert_eq!(
+        gate.check(&enter_order(2, Side::Buy, 450)),
+        Err(RiskViolation::MaxNotional {
+            notional: 450 * 3_5000,
+            limit: 400 * 3_5000
+        })
+    );
+
+    gate.restrict_symbol(stonks());
+    assert_eq!(
+        gate.check(&enter_order(2, Side::Buy, 100)),
+        Err(RiskViolation::RestrictedSymbol(stonks()))
+    );
+
+    // Cancels are never refused.
+    assert!(gate.check(&cancel!{
+        user_ref_num: user_ref_num(1),
+        quantity: 0u32,
+    }).is_ok());
+
+    assert_eq!(refused.lock().unwrap().len(), 4);
+}
+
+#[test] fn open_exposure() {
+
+    let mut gate = RiskGate::new();
+    gate.set_max_symbol_exposure(500 * 3_5000);
+
+    let enter = enter_order(1, Side::Buy, 300);
+    assert!(gate.check(&enter).is_ok());
+
+    // Allowed requests only count once they are tracked as sent.
+    assert_eq!(gate.exposure(), 0);
+    gate.track_request(&enter);
+    assert_eq!(gate.exposure(), 300 * 3_5000);
+
+    gate.track_response(&accepted(1, b'B', 300, b'L'));
+    assert_eq!(gate.symbol_exposure(&stonks()), 300 * 3_5000);
+
+    assert_eq!(
+        gate.check(&enter_order(2, Side::Sell, 300)),
+        Err(RiskViolation::SymbolExposure {
+            symbol: stonks(),
+            exposure: 600 * 3_5000,
+            limit: 500 * 3_5000,
+        })
+    );
+
+    // A replacement releases the exposure of the order it replaces.
+    assert!(gate.check(&replace!{
+        old_ref_num: user_ref_num(1),
+        new_ref_num: user_ref_num(2),
+        quantity: 400u32,
+        price: Price::new(3_5000).unwrap(),
+        time_in_force: TimeInForce::Day,
+        display: Display::Visible,
+        intermarket_sweep: false,
+        order_token: OrderToken::from("Replaced").unwrap()
+    }).is_ok());
+
+    // Canceled shares no longer count.
+    gate.track_response(&canceled(1, 300));
+    gate.track_response(&accepted(2, b'B', 400, b'L'));
+    assert_eq!(gate.exposure(), 400 * 3_5000);
+
+    gate.set_max_exposure(450 * 3_5000);
+    assert_eq!(
+        gate.check(&enter_order(3, Side::Buy, 100)),
+        Err(RiskViolation::TotalExposure {
+            exposure: 500 * 3_5000,
+            limit: 450 * 3_5000,
+        })
+    );
+}
+
+#[test] fn check_and_track() {
+
+    let mut gate = RiskGate::new();
+    gate.set_max_exposure(500 * 3_5000);
+
+    // Allowed requests count toward the limits at once.
+    let enter = enter_order(1, Side::Buy, 300);
+    assert!(gate.check_and_track(&enter).is_ok());
+    assert_eq!(gate.exposure(), 300 * 3_5000);
+    assert!(gate.check_and_track(&enter_order(2, Side::Buy, 300)).is_err());
+
+    // Until they turn out not to have been sent.
+    gate.untrack_request(&enter);
+    assert_eq!(gate.exposure(), 0);
+    assert!(gate.check_and_track(&enter_order(2, Side::Buy, 300)).is_ok());
+}
+
+#[test] fn extreme_values() {
+
+    let mut gate = RiskGate::new();
+    gate.set_max_exposure(u64::MAX - 1);
+
+    // Orders accepted with the largest quantity, at an unchecked price.
+    for num in 1..=2 {
+        let mut data = accepted(num, b'B', u32::MAX, b'L').encode();
+        data[26..34].copy_from_slice(&u64::MAX.to_be_bytes());
+        gate.track_response(&response(data));
+    }
+
+    // Exposures too large to count are over any limit.
+    assert_eq!(gate.exposure(), u64::MAX);
+    assert_eq!(
+        gate.check(&enter_order(3, Side::Buy, 100)),
+        Err(RiskViolation::TotalExposure {
+            exposure: u64::MAX,
+            limit: u64::MAX - 1,
+        })
+    );
+}
@@ -0,0 +1,90 @@
+
+use crate::{
+    msg::OuchResponse,
+    soupbintcp::*,
+    error::OuchError,
+};
+
+
+// Sequenced SystemEvent packet, including the length marker.
+fn system_event() -> Vec<u8> {
+
+    let mut data: Vec<u8> = 11u16.to_be_bytes().to_vec();
+    data.push(b'S');
+    data.push(b'S');
+    data.extend(1u64.to_be_bytes());
+    data.push(b'S');
+    data
+}
+
+#[test] fn coalesced_packets() {
+
+    let mut buffer = PacketBuffer::new();
+
+    // Two packets arrive in the same read.
+    let mut data = system_event();
+    data.extend(system_event());
+    buffer.extend(&data);
+
+    for _ in 0..2 {
+        match buffer.next_packet().unwrap() {
+            Some(ServerPacket::SequencedData(OuchResponse::SystemEvent(..))) 
+                => {},
+            _ => panic!("Damn, son, where'd you find this?")
+        }
+    }
+
+    assert!(buffer.next_packet().unwrap().is_none());
+    assert!(buffer.is_empty());
+}
+
+#[test] fn partial_packets() {
+
+    let mut buffer = PacketBuffer::new();
+
+    // One and a half packets arrive in the first read.
+    let mut data = system_event();
+    data.extend(system_event());
+    let (first, second) = data.split_at(20);
+
+    buffer.extend(first);
+    assert!(buffer.next_packet().unwrap().is_some());
+    assert!(buffer.next_packet().unwrap().is_none());
+    assert_eq!(buffer.len(), 7);
+
+    // The rest arrives in the next read.
+    buffer.extend(second);
+    assert!(buffer.next_packet().unwrap().is_some());
+    assert!(buffer.is_empty());
+}
+
+#[test] fn large_packet() {
+
+    let mut buffer = PacketBuffer::new();
+
+    let text = "X".repeat(1000);
+    let data = ClientPacket::Debug(text.clone()).encode().unwrap();
+
+    // Arrives one byte at a time.
+    for byte in data.iter() {
+        assert!(buffer.next_packet().unwrap().is_none());
+        buffer.extend(&[*byte]);
+    }
+
+    let packet = buffer.next_packet().unwrap();
+    assert_eq!(packet, Some(ServerPacket::Debug(text)));
+}
+
+#[test] fn skip_bad_packet() {
+
+    let mut buffer = PacketBuffer::new();
+
+    // Unknown packet type, followed by a good packet.
+    let mut data = vec![0, 2, b'?', b'?'];
+    data.extend(system_event());
+    buffer.extend(&data);
+
+    assert!(matches!(buffer.next_packet(), Err(OuchError::Parse(..))));
+    assert!(buffer.next_packet().unwrap().is_some());
+    assert!(buffer.is_empty());
+}
@@ -0,0 +1,119 @@
+
+use crate::{
+    account_query,
+    soupbintcp::*,
+    error::OuchError,
+};
+
+
+#[test] fn encode_login_request() {
+
+    let packet = ClientPacket::LoginRequest {
+        username: Username::from("USER").unwrap(),
+        password: Password::from("PASSWORD").unwrap(),
+        session: SessionId::from("SESSION1").unwrap(),
+        sequence_number: 1u64,
+    };
+
+    let bytes = packet.encode().unwrap();
+
+    // Packet length, including the packet type
+    let mut should_be: Vec<u8> = 47u16.to_be_bytes().to_vec();
+    // Packet type
+    should_be.push(b'L');
+    // Username (padded on the right)
+    should_be.extend(b"USER  ");
+    // Password (padded on the right)
+    should_be.extend(b"PASSWORD  ");
+    // Requested session (padded on the left)
+    should_be.extend(b"  SESSION1");
+    // Requested sequence number (padded on the left)
+    should_be.extend(b"                   1");
+    assert_eq!(bytes, should_be);
+}
+
+#[test] fn encode_login_request_current_session() {
+
+    let packet = ClientPacket::LoginRequest {
+        username: Username::from("USER").unwrap(),
+        password: Password::from("PASSWORD").unwrap(),
+        session: SessionId::current(),
+        sequence_number: 0u64,
+    };
+
+    let bytes = packet.encode().unwrap();
+    assert_eq!(&bytes[19..29], b"          ");
+    assert_eq!(&bytes[29..], b"                   0");
+}
+
+#[test] fn encode_unsequenced_data() {
+
+    let packet = ClientPacket::UnsequencedData(account_query!());
+    let bytes = packet.encode().unwrap();
+
+    // Packet length, including the packet type
+    let mut should_be: Vec<u8> = 4u16.to_be_bytes().to_vec();
+    // Packet type
+    should_be.push(b'U');
+    // OUCH message
+    should_be.push(b'Q');
+    should_be.extend(0u16.to_be_bytes());
+    assert_eq!(bytes, should_be);
+}
+
+#[test] fn encode_session_packets() {
+
+    let bytes = ClientPacket::ClientHeartbeat.encode().unwrap();
+    assert_eq!(bytes, vec![0, 1, b'R']);
+    let bytes = ClientPacket::LogoutRequest.encode().unwrap();
+    assert_eq!(bytes, vec![0, 1, b'O']);
+
+    let bytes = ClientPacket::Debug("hi".to_string()).encode().unwrap();
+    assert_eq!(bytes, vec![0, 3, b'+', b'h', b'i']);
+}
+
+#[test] fn encode_long_packet() {
+
+    // The length counts the packet type, so this is the longest payload.
+    let text = "X".repeat(65534);
+    let bytes = ClientPacket::Debug(text).encode().unwrap();
+    assert_eq!(bytes[..2], [0xFF, 0xFF]);
+
+    let text = "X".repeat(65535);
+    match ClientPacket::Debug(text).encode() {
+        Err(OuchError::PacketTooLong(length)) => assert_eq!(length, 65536),
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+}
+
+#[test] fn bad_credentials() {
+    assert!(Username::from("USER!").is_err());
+    assert!(Password::from("PASS-WORD").is_err());
+    assert!(SessionId::from("SESSION_1").is_err());
+}
+
+#[test] fn parse_client_packets() {
+
+    let login = ClientPacket::LoginRequest {
+        username: Username::from("USER").unwrap(),
+        password: Password::from("PASSWORD").unwrap(),
+        session: SessionId::from("SESSION1").unwrap(),
+        sequence_number: 42u64,
+    };
+
+    let mut data = login.encode().unwrap();
+    data.extend(
+        ClientPacket::UnsequencedData(account_query!()).encode().unwrap()
+    );
+    data.extend(ClientPacket::ClientHeartbeat.encode().unwrap());
+
+    let (data, packet) = ClientPacket::parse(&data).unwrap();
+    assert_eq!(packet, login);
+
+    let (data, packet) = ClientPacket::parse(data).unwrap();
+    assert_eq!(packet, ClientPacket::UnsequencedData(account_query!()));
+
+    let (data, packet) = ClientPacket::parse(data).unwrap();
+    assert_eq!(packet, ClientPacket::ClientHeartbeat);
+    assert!(data.is_empty());
+}
@@ -0,0 +1,4 @@
+
+#[cfg(test)] mod client;
+#[cfg(test)] mod server;
+#[cfg(test)] mod buffer;
@@ -0,0 +1,157 @@
+
+use nom::error::ErrorKind;
+
+use crate::{
+    msg::OuchResponse,
+    soupbintcp::*,
+    error::ParseErrorKind,
+};
+
+
+#[test] fn parse_login_accepted() {
+
+    // Packet length, including the packet type
+    let mut data: Vec<u8> = 31u16.to_be_bytes().to_vec();
+    // Packet type
+    data.push(b'A');
+    // Session (padded on the left)
+    data.extend(b"  SESSION1");
+    // Sequence number (padded on the left)
+    data.extend(b"                  42");
+
+    let (data, packet) = ServerPacket::parse(&data).unwrap();
+    assert!(data.is_empty());
+
+    match packet {
+        ServerPacket::LoginAccepted { session, sequence_number } => {
+            assert_eq!(session, SessionId::from("SESSION1").unwrap());
+            assert_eq!(session.to_str(), "SESSION1");
+            assert_eq!(sequence_number, 42u64);
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+}
+
+#[test] fn parse_login_rejected() {
+
+    let data = vec![0, 2, b'J', b'A'];
+    let (_data, packet) = ServerPacket::parse(&data).unwrap();
+    assert_eq!(
+        packet, 
+        ServerPacket::LoginRejected(LoginRejectReason::NotAuthorized)
+    );
+
+    let data = vec![0, 2, b'J', b'S'];
+    let (_data, packet) = ServerPacket::parse(&data).unwrap();
+    assert_eq!(
+        packet, 
+        ServerPacket::LoginRejected(LoginRejectReason::SessionNotAvailable)
+    );
+}
+
+#[test] fn parse_sequenced_data() {
+
+    // Packet length, including the packet type
+    let mut data: Vec<u8> = 11u16.to_be_bytes().to_vec();
+    // Packet type
+    data.push(b'S');
+    // OUCH message
+    data.push(b'S');
+    data.extend(1u64.to_be_bytes());
+    data.push(b'S');
+
+    let (data, packet) = ServerPacket::parse(&data).unwrap();
+    assert!(data.is_empty());
+
+    match packet {
+        ServerPacket::SequencedData(OuchResponse::SystemEvent(..)) => {},
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+}
+
+#[test] fn parse_session_packets() {
+
+    let data = vec![0, 1, b'H', 0, 1, b'Z', 0, 3, b'+', b'h', b'i'];
+
+    let (data, packet) = ServerPacket::parse(&data).unwrap();
+    assert_eq!(packet, ServerPacket::ServerHeartbeat);
+    let (data, packet) = ServerPacket::parse(data).unwrap();
+    assert_eq!(packet, ServerPacket::EndOfSession);
+    let (data, packet) = ServerPacket::parse(data).unwrap();
+    assert_eq!(packet, ServerPacket::Debug("hi".to_string()));
+    assert!(data.is_empty());
+}
+
+#[test] fn parse_incomplete() {
+
+    // Length marker is incomplete
+    let data = vec![0];
+    let err = ServerPacket::parse(&data).unwrap_err();
+    assert!(err.is_incomplete());
+    assert_eq!(err.field(), Some("length"));
+
+    // Packet is incomplete
+    let data = vec![0, 2, b'J'];
+    let err = ServerPacket::parse(&data).unwrap_err();
+    assert_eq!(err.kind(), ParseErrorKind::Incomplete(Some(1)));
+    assert_eq!(err.field(), Some("packet"));
+
+    // Sequenced message is too short for its type, but the packet is whole
+    let data = vec![0, 3, b'S', b'S', 0];
+    let err = ServerPacket::parse(&data).unwrap_err();
+    assert_eq!(err.kind(), ParseErrorKind::Invalid(ErrorKind::Complete));
+    assert_eq!(err.message_type(), Some('S'));
+    assert_eq!(err.field(), Some("timestamp"));
+    assert_eq!(err.offset(), 1);
+    assert_eq!(err.bytes(), &[b'S', 0]);
+}
+
+#[test] fn parse_truncated_appendage() {
+
+    // An Order Canceled that is missing its appendage length marker.
+    let mut message = vec![b'C'];
+    message.extend(1u64.to_be_bytes());
+    message.extend(1u32.to_be_bytes());
+    message.extend(0u32.to_be_bytes());
+    message.push(b'H');
+
+    let err = OuchResponse::parse(&message).unwrap_err();
+    assert!(err.is_incomplete());
+    assert_eq!(err.field(), Some("options length"));
+
+    // Within a whole packet, it can never be completed.
+    let mut data = (message.len() as u16 + 1).to_be_bytes().to_vec();
+    data.push(b'S');
+    data.extend(&message);
+    let err = ServerPacket::parse(&data).unwrap_err();
+    assert_eq!(err.kind(), ParseErrorKind::Invalid(ErrorKind::Complete));
+    assert_eq!(err.message_type(), Some('C'));
+    assert_eq!(err.field(), Some("options length"));
+}
+
+#[test] fn parse_unknown_packet() {
+    let data = vec![0, 1, b'?'];
+    let err = ServerPacket::parse(&data).unwrap_err();
+    assert_eq!(err.kind(), ParseErrorKind::Invalid(ErrorKind::Tag));
+    assert_eq!(err.field(), Some("packet type"));
+}
+
+#[test] fn encode_server_packets() {
+
+    let packets = vec![
+        ServerPacket::LoginAccepted {
+            session: SessionId::from("SESSION1").unwrap(),
+            sequence_number: 42u64,
+        },
+        ServerPacket::LoginRejected(LoginRejectReason::SessionNotAvailable),
+        ServerPacket::ServerHeartbeat,
+        ServerPacket::EndOfSession,
+    ];
+
+    for packet in packets {
+        let data = packet.encode().unwrap();
+        let (data, parsed) = ServerPacket::parse(&data).unwrap();
+        assert_eq!(parsed, packet);
+        assert!(data.is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+
+use std::{
+    io::{ Read, Write },
+    net::{ TcpListener, TcpStream },
+    sync::{ Arc, Mutex },
+};
+
+use crate::{
+    OuchSupervisor,
+    msg::OuchResponse,
+    soupbintcp::*,
+    supervisor::ConnectionState,
+    types::EventCode,
+};
+
+use super::client::{
+    login_accepted,
+    account_query_response,
+    system_event,
+};
+
+
+// Read a login request and reply with Login Accepted.
+// Returns the session and sequence number that were requested.
+fn accept_login(stream: &mut TcpStream, sequence: u64) -> (String, String) {
+
+    let mut request = [0u8; 49];
+    stream.read_exact(&mut request).unwrap();
+    assert_eq!(request[2], b'L');
+
+    let session = String::from_utf8(request[19..29].to_vec()).unwrap();
+    let requested = String::from_utf8(request[29..49].to_vec()).unwrap();
+
+    stream.write_all(&login_accepted(sequence)).unwrap();
+
+    (session, requested)
+}
+
+// Read an AccountQuery request.
+fn read_query(stream: &mut TcpStream) {
+
+    let mut request = [0u8; 6];
+    stream.read_exact(&mut request).unwrap();
+    assert_eq!(&request[2..4], b"UQ");
+}
+
+// Serve two connections, losing the first after two sequenced messages.
+fn mock_server(listener: TcpListener) {
+
+    let (mut stream, _) = listener.accept().unwrap();
+    let (session, sequence) = accept_login(&mut stream, 1);
+    assert_eq!(session.trim(), "");
+    assert_eq!(sequence.trim(), "0");
+
+    read_query(&mut stream);
+    stream.write_all(&account_query_response(5)).unwrap();
+    stream.write_all(&system_event(b'S')).unwrap();
+    drop(stream);
+
+    // Second connection resumes the session at the third message,
+    // which is replayed ahead of the new AccountQueryResponse.
+    let (mut stream, _) = listener.accept().unwrap();
+    let (session, sequence) = accept_login(&mut stream, 3);
+    assert_eq!(session.trim(), "SESS1");
+    assert_eq!(sequence.trim(), "3");
+
+    read_query(&mut stream);
+    stream.write_all(&system_event(b'E')).unwrap();
+    stream.write_all(&account_query_response(2)).unwrap();
+
+    // Wait for the client to hang up.
+    let _ = stream.read(&mut [0u8; 16]);
+}
+
+fn reconnected(addr: std::net::SocketAddr) -> Vec<ConnectionState> {
+    vec![
+        ConnectionState::Connecting(addr),
+        ConnectionState::Connected(addr),
+        ConnectionState::Disconnected,
+        ConnectionState::Connecting(addr),
+        ConnectionState::Connected(addr),
+    ]
+}
+
+#[test] fn reconnect_and_resume() {
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = std::thread::spawn(move || mock_server(listener));
+
+    let mut supervisor = OuchSupervisor::new(
+        vec![addr],
+        Username::from("USER").unwrap(),
+        Password::from("PASSWORD").unwrap(),
+        0u64,
+    );
+
+    let states = Arc::new(Mutex::new(Vec::new()));
+    let record = states.clone();
+    supervisor.on_state_change(move |state| {
+        record.lock().unwrap().push(state)
+    });
+
+    match supervisor.recv().unwrap() {
+        OuchResponse::SystemEvent(event) => {
+            assert_eq!(event.event_code(), EventCode::StartOfDay)
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    assert_eq!(supervisor.sequence_number(), 3u64);
+
+    match supervisor.recv().unwrap() {
+        OuchResponse::SystemEvent(event) => {
+            assert_eq!(event.event_code(), EventCode::EndOfDay)
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    assert_eq!(supervisor.session().to_str(), "SESS1");
+
+    // The older UserRefNum from the second sync is not used.
+    assert_eq!(supervisor.new_user_ref_num().val(), 5u32);
+
+    drop(supervisor);
+    server.join().unwrap();
+
+    assert_eq!(*states.lock().unwrap(), reconnected(addr));
+}
+
+#[cfg(feature = "async")]
+#[tokio::test] async fn async_reconnect_and_resume() {
+
+    use crate::AsyncOuchSupervisor;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = std::thread::spawn(move || mock_server(listener));
+
+    let mut supervisor = AsyncOuchSupervisor::new(
+        vec![addr],
+        Username::from("USER").unwrap(),
+        Password::from("PASSWORD").unwrap(),
+        0u64,
+        std::time::Duration::from_secs(5),
+    );
+
+    let states = Arc::new(Mutex::new(Vec::new()));
+    let record = states.clone();
+    supervisor.on_state_change(move |state| {
+        record.lock().unwrap().push(state)
+    });
+
+    match supervisor.recv().await.unwrap() {
+        OuchResponse::SystemEvent(event) => {
+            assert_eq!(event.event_code(), EventCode::StartOfDay)
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+
+    match supervisor.recv().await.unwrap() {
+        OuchResponse::SystemEvent(event) => {
+            assert_eq!(event.event_code(), EventCode::EndOfDay)
+        },
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+    assert_eq!(supervisor.new_user_ref_num().val(), 5u32);
+
+    drop(supervisor);
+    server.join().unwrap();
+
+    assert_eq!(*states.lock().unwrap(), reconnected(addr));
+}
@@ -0,0 +1,122 @@
+
+use std::time::Duration;
+
+use crate::{
+    enter,
+    cancel,
+    replace,
+    mass_cancel,
+    msg::OuchRequest,
+    throttle::Throttle,
+    types::*,
+};
+
+use super::orders::{ user_ref_num, enter_order };
+
+
+fn enter_meme(num: u32) -> OuchRequest {
+    enter!{
+        user_ref_num: user_ref_num(num),
+        side: Side::Buy,
+        quantity: 100,
+        symbol: StockSymbol::from("MEME").unwrap(),
+        price: Price::new(35000).unwrap(),
+        time_in_force: TimeInForce::Day,
+        display: Display::Visible,
+        capacity: Capacity::Agency,
+        intermarket_sweep: false,
+        cross_type: CrossType::ContinuousMarket,
+        order_token: OrderToken::from("To The Moon").unwrap()
+    }
+}
+
+
+#[test] fn port_rate() {
+
+    let mut throttle = Throttle::new();
+    throttle.set_port_rate(1, 2);
+
+    assert!(throttle.acquire(&enter_order(1, Side::Buy, 100)).is_ok());
+    assert!(throttle.acquire(&enter_meme(2)).is_ok());
+
+    match throttle.acquire(&enter_order(3, Side::Buy, 100)) {
+        Err(wait) => assert!(wait > Duration::ZERO),
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+
+    // Cancels are never held back, but they still use up the limit.
+    assert!(throttle.acquire(&cancel!{
+        user_ref_num: user_ref_num(1),
+        quantity: 0u32,
+    }).is_ok());
+    assert!(throttle.acquire(&mass_cancel!{
+        user_ref_num: user_ref_num(4),
+        firm: Mpid::from("FIRM").unwrap(),
+        symbol: StockSymbol::from("STONKS").unwrap(),
+    }).is_ok());
+
+    match throttle.acquire(&enter_order(3, Side::Buy, 100)) {
+        Err(wait) => assert!(wait > Duration::from_secs(1)),
+        _ => panic!("Damn, son, where'd you find this?")
+    }
+}
+
+#[test] fn symbol_rate() {
+
+    let mut throttle = Throttle::new();
+    throttle.set_symbol_rate(1, 1);
+
+    assert!(throttle.acquire(&enter_order(1, Side::Buy, 100)).is_ok());
+    assert!(throttle.acquire(&enter_order(2, Side::Buy, 100)).is_err());
+
+    // Each symbol has its own limit.
+    assert!(throttle.acquire(&enter_meme(3)).is_ok());
+
+    // Replacements count toward the symbol of the replaced order.
+    let replace = replace!{
+        old_ref_num: user_ref_num(1),
+        new_ref_num: user_ref_num(4),
+        quantity: 100u32,
+        price: Price::new(35000).unwrap(),
+        time_in_force: TimeInForce::Day,
+        display: Display::Visible,
+        intermarket_sweep: false,
+        order_token: OrderToken::from("To The Moon").unwrap()
+    };
+    assert!(throttle.acquire(&replace).is_err());
+
+    throttle.set_symbol_rate(1, 2);
+    assert!(throttle.acquire(&replace).is_ok());
+    assert!(throttle.acquire(&enter_order(5, Side::Buy, 100)).is_ok());
+    assert!(throttle.acquire(&enter_meme(6)).is_ok());
+    assert!(throttle.acquire(&enter_meme(7)).is_ok());
+    assert!(throttle.acquire(&enter_meme(8)).is_err());
+}
+
+#[test] fn forget_idle_orders() {
+
+    let mut throttle = Throttle::new();
+    throttle.set_symbol_rate(10, 1);
+
+    assert!(throttle.acquire(&enter_order(1, Side::Buy, 100)).is_ok());
+
+    // Order 1 is forgotten once it has been idle for longer than
+    // the symbol limit takes to refill.
+    std::thread::sleep(Duration::from_millis(150));
+    assert!(throttle.acquire(&enter_order(2, Side::Buy, 100)).is_ok());
+
+    let replace = |old: u32, new: u32| replace!{
+        old_ref_num: user_ref_num(old),
+        new_ref_num: user_ref_num(new),
+        quantity: 100u32,
+        price: Price::new(35000).unwrap(),
+        time_in_force: TimeInForce::Day,
+        display: Display::Visible,
+        intermarket_sweep: false,
+        order_token: OrderToken::from("To The Moon").unwrap()
+    };
+
+    // Only order 2 still counts toward the symbol.
+    assert!(throttle.acquire(&replace(2, 3)).is_err());
+    assert!(throttle.acquire(&replace(1, 4)).is_ok());
+}
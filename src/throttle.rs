@@ -0,0 +1,277 @@
+
+use std::collections::HashMap;
+use std::time::{ Duration, Instant };
+
+use crate::{
+    msg::OuchRequest,
+    error::OuchError,
+    types::UserRefNum,
+};
+
+
+/// What a throttled client does with a request that would exceed a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThrottleMode {
+
+    /// Wait in `send` until the request can be sent.
+    #[default]
+    Queue,
+
+    /// Return `OuchError::Throttled` with the time to wait.
+    Refuse,
+}
+
+
+/// Limits the rate of requests sent to the server, per port (i.e., per
+/// connection) and per `StockSymbol`, so that they are not rejected by
+/// the exchange for exceeding its message rates.
+///
+/// Each limit is a token bucket, which allows bursts of up to `burst`
+/// requests and refills at `per_second` requests each second.
+///
+/// Risk-reducing requests (`CancelOrder`, `MassCancel` and
+/// `DisableOrderEntry`) are never delayed or refused.
+/// They still count toward the limits, delaying the requests after them.
+///
+/// Requests that refer to an earlier order count toward its symbol.
+/// The symbol of an order is forgotten once no request has been sent for
+/// it in longer than the symbol limit takes to refill (`burst` divided by
+/// `per_second`); later requests for that order only count toward the
+/// port limit.
+///
+/// Use `OuchClient::set_throttle` (or the same method on any client or
+/// supervisor) to throttle every request sent through `send`.
+#[derive(Debug, Clone, Default)]
+pub struct Throttle {
+    mode: ThrottleMode,
+    port: Option<TokenBucket>,
+    symbol_limit: Option<TokenBucket>,
+    symbols: HashMap<[u8; 8], TokenBucket>,
+    // Requests that refer to an earlier order take its symbol,
+    // which is kept until the order has been idle for a whole window.
+    order_symbols: HashMap<UserRefNum, ([u8; 8], Instant)>,
+    pruned: Option<Instant>,
+}
+
+impl Throttle {
+
+    /// Create a throttle with no limits.
+    pub fn new() -> Self {
+        Throttle::default()
+    }
+
+    /// Choose whether requests over the limit wait or are refused.
+    /// Defaults to `ThrottleMode::Queue`.
+    pub fn set_mode(&mut self, mode: ThrottleMode) {
+        self.mode = mode
+    }
+
+    /// What is done with requests over the limit.
+    pub fn mode(&self) -> ThrottleMode { self.mode }
+
+    /// Limit the rate of all requests sent on the connection.
+    pub fn set_port_rate(&mut self, per_second: u32, burst: u32) {
+        self.port = Some(TokenBucket::new(per_second, burst))
+    }
+
+    /// Limit the rate of requests sent for each symbol.
+    pub fn set_symbol_rate(&mut self, per_second: u32, burst: u32) {
+        self.symbol_limit = Some(TokenBucket::new(per_second, burst));
+        self.symbols.clear();
+    }
+
+    /// Take the capacity to send a request,
+    /// or return how long to wait before it can be sent.
+    pub fn acquire(&mut self, request: &OuchRequest) -> Result<(), Duration> {
+
+        let now = Instant::now();
+        self.prune(now);
+        let symbol = self.symbol(request, now);
+
+        if !is_risk_reducing(request) {
+
+            let mut wait = Duration::ZERO;
+            if let Some(port) = &mut self.port {
+                wait = wait.max(port.wait(now));
+            }
+            if let Some(bucket) = symbol.and_then(|s| self.bucket(s)) {
+                wait = wait.max(bucket.wait(now));
+            }
+
+            if !wait.is_zero() {
+                return Err(wait)
+            }
+        }
+
+        if let Some(port) = &mut self.port {
+            port.take(now);
+        }
+        if let Some(bucket) = symbol.and_then(|s| self.bucket(s)) {
+            bucket.take(now);
+        }
+
+        Ok(())
+    }
+
+    // Requests over the limit either wait (for the returned duration,
+    // after which they are tried again) or are refused.
+    pub(crate) fn admit(
+        &mut self,
+        request: &OuchRequest
+    ) -> Result<Option<Duration>, OuchError> {
+
+        let Err(wait) = self.acquire(request) else {
+            return Ok(None)
+        };
+
+        match self.mode {
+            ThrottleMode::Queue => Ok(Some(wait)),
+            ThrottleMode::Refuse => {
+
+                #[cfg(feature = "logs")] {
+                    log::warn!(
+                        "Throttle refused {} request for {:?}",
+                        request,
+                        wait
+                    );
+                }
+
+                Err(OuchError::Throttled(wait))
+            },
+        }
+    }
+
+    fn bucket(&mut self, symbol: [u8; 8]) -> Option<&mut TokenBucket> {
+        let limit = self.symbol_limit.as_ref()?;
+        Some(self.symbols.entry(symbol).or_insert_with(|| limit.clone()))
+    }
+
+    // Find the symbol of a request, remembering the symbol of each order.
+    fn symbol(
+        &mut self,
+        request: &OuchRequest,
+        now: Instant
+    ) -> Option<[u8; 8]> {
+
+        use OuchRequest::*;
+        match request {
+
+            EnterOrder(enter) => {
+                let symbol = enter.symbol().encode();
+                self.remember(enter.user_ref_num(), symbol, now);
+                Some(symbol)
+            },
+
+            ReplaceOrder(replace) => {
+                let symbol = self.recall(replace.old_ref_num(), now)?;
+                self.remember(replace.new_ref_num(), symbol, now);
+                Some(symbol)
+            },
+
+            ModifyOrder(modify) => self.recall(modify.user_ref_num(), now),
+            CancelOrder(cancel) => self.recall(cancel.user_ref_num(), now),
+            MassCancel(cancel) => Some(cancel.symbol().encode()),
+
+            _ => None,
+        }
+    }
+
+    // Symbols are only needed for the symbol limit.
+    fn remember(&mut self, order: UserRefNum, symbol: [u8; 8], now: Instant) {
+        if self.symbol_limit.is_some() {
+            self.order_symbols.insert(order, (symbol, now));
+        }
+    }
+
+    fn recall(&mut self, order: UserRefNum, now: Instant) -> Option<[u8; 8]> {
+        let (symbol, used) = self.order_symbols.get_mut(&order)?;
+        *used = now;
+        Some(*symbol)
+    }
+
+    // Forget the symbols of orders that have been idle for a whole window,
+    // so that they do not build up over a session.
+    // This is done at most once per window.
+    fn prune(&mut self, now: Instant) {
+
+        let Some(limit) = &self.symbol_limit else {
+            self.order_symbols.clear();
+            return
+        };
+
+        let window = limit.window();
+        if self.pruned.is_some_and(|at| now.duration_since(at) < window) {
+            return
+        }
+
+        self.order_symbols
+            .retain(|_, (_, used)| now.duration_since(*used) < window);
+        self.pruned = Some(now);
+    }
+
+    /// Forget the symbol of every order seen so far (e.g., between sessions).
+    /// Later requests for those orders only count toward the port limit.
+    pub fn clear_orders(&mut self) {
+        self.order_symbols.clear()
+    }
+}
+
+// These may never wait behind new orders.
+fn is_risk_reducing(request: &OuchRequest) -> bool {
+    matches!(request,
+        OuchRequest::CancelOrder(..) |
+        OuchRequest::MassCancel(..) |
+        OuchRequest::DisableOrderEntry(..)
+    )
+}
+
+
+// Allows bursts of `burst` requests, refilling at `rate` per second.
+// Risk-reducing requests may overdraw the bucket.
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+
+    // A bucket that never refills would hold requests forever.
+    fn new(per_second: u32, burst: u32) -> Self {
+        TokenBucket {
+            rate: per_second.max(1) as f64,
+            burst: burst.max(1) as f64,
+            tokens: burst.max(1) as f64,
+            updated: Instant::now(),
+        }
+    }
+
+    // Time to refill completely from empty.
+    fn window(&self) -> Duration {
+        Duration::from_secs_f64(self.burst / self.rate)
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.rate)
+            .min(self.burst);
+        self.updated = now;
+    }
+
+    fn wait(&mut self, now: Instant) -> Duration {
+
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            return Duration::ZERO
+        }
+
+        Duration::from_secs_f64((1.0 - self.tokens) / self.rate)
+    }
+
+    fn take(&mut self, now: Instant) {
+        self.refill(now);
+        self.tokens -= 1.0;
+    }
+}
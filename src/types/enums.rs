@@ -171,6 +171,7 @@ define_enum!{
         "No handling instructions.",
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for HandleInst {
     fn default() -> Self {
         HandleInst::NoInstructions
@@ -308,6 +309,7 @@ define_enum!{
         Not usable with `OptionValue::DiscretionPriceType`.",
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for PriceType {
     fn default() -> Self {
         PriceType::Limit
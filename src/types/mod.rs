@@ -10,7 +10,7 @@ mod elapsed_time;
 pub use self::{
     enums::*,
     order_token::OrderToken,
-    user_ref_num::UserRefNum,
+    user_ref_num::{ UserRefNum, UserRefNumGenerator },
     route_id::RouteId,
     elapsed_time::ElapsedTime,
 };
@@ -1,4 +1,10 @@
 
+use std::sync::{
+    Arc,
+    atomic::{ AtomicU32, Ordering },
+};
+
+
 /// Unsigned number used to differentiate requests.
 /// These are day-unique and must be strictly increasing for each new number.
 ///
@@ -11,7 +17,7 @@
 ///
 /// If you may need to refer to an order at a later time, 
 /// be sure to copy and save its UserRefNum somewhere in your code.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct UserRefNum(u32);
 
 impl UserRefNum {
@@ -50,4 +56,48 @@ impl UserRefNum {
     }
 }
 
+impl Default for UserRefNum {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Shared source of new UserRefNums, for when requests are created
+/// on several threads or tasks at once.
+/// Every clone draws from the same sequence, so no number is used twice.
+#[derive(Debug, Clone)]
+pub struct UserRefNumGenerator(Arc<AtomicU32>);
+
+impl UserRefNumGenerator {
+
+    /// Start a new sequence, beginning with `num`.
+    pub fn new(num: UserRefNum) -> Self {
+        UserRefNumGenerator(Arc::new(AtomicU32::new(num.val())))
+    }
+
+    /// Use this method to GET the next valid UserRefNum.
+    /// See `OuchClient::new_user_ref_num`.
+    pub fn new_user_ref_num(&self) -> UserRefNum {
+        UserRefNum(self.0.fetch_add(1, Ordering::SeqCst))
+    }
+
+    /// Use this method to PEEK at the next UserRefNum.
+    /// See `OuchClient::next_user_ref_num`.
+    pub fn next_user_ref_num(&self) -> UserRefNum {
+        UserRefNum(self.0.load(Ordering::SeqCst))
+    }
+
+    /// Ensure the next UserRefNum is at least `num`.
+    /// The next UserRefNum is never decreased.
+    pub fn sync_user_ref_num(&self, num: UserRefNum) {
+        self.0.fetch_max(num.val(), Ordering::SeqCst);
+    }
+}
+
+impl Default for UserRefNumGenerator {
+    fn default() -> Self {
+        Self::new(UserRefNum::new())
+    }
+}
 